@@ -0,0 +1,51 @@
+//! Compares the schedulers on many tiny jobs which spawn further jobs
+//!
+//! Run with `cargo bench --bench schedulers`.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use threatpool::{Scheduler, ThreadPool, ThreadPoolBuilder};
+
+/// The number of top-level jobs
+const JOBS: usize = 1_000;
+
+/// The number of jobs each top-level job spawns
+const FAN_OUT: usize = 1_000;
+
+fn run(pool: &ThreadPool) -> Duration {
+    let counter = Arc::new(AtomicUsize::new(0));
+    let start = Instant::now();
+
+    for _ in 0..JOBS {
+        let inner_pool = pool.clone();
+        let counter = Arc::clone(&counter);
+
+        pool.execute(move || {
+            for _ in 0..FAN_OUT {
+                let counter = Arc::clone(&counter);
+                inner_pool.execute(move || {
+                    counter.fetch_add(1, Ordering::Relaxed);
+                });
+            }
+        });
+    }
+
+    pool.join();
+    assert_eq!(counter.load(Ordering::SeqCst), JOBS * FAN_OUT);
+
+    start.elapsed()
+}
+
+fn main() {
+    for scheduler in [Scheduler::GlobalQueue, Scheduler::WorkStealing] {
+        let pool = ThreadPoolBuilder::new().scheduler(scheduler).build();
+
+        // Warm up the workers first
+        run(&pool);
+
+        let elapsed = run(&pool);
+        println!("{scheduler:?}: {elapsed:?} for {} jobs", JOBS * FAN_OUT);
+    }
+}
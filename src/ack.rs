@@ -0,0 +1,45 @@
+use std::cell::Cell;
+use std::sync::mpsc;
+
+/// Notified once a job submitted with
+/// [`ThreadPool::execute_acked`](crate::ThreadPool::execute_acked) starts running
+///
+/// Unlike the queue filling up, this reflects when a worker actually picks
+/// up the job, so a producer can bound the number of jobs it is ahead by.
+#[derive(Debug)]
+pub struct AckReceiver {
+    receiver: mpsc::Receiver<()>,
+
+    /// Whether the ack has already been received
+    started: Cell<bool>,
+}
+
+impl AckReceiver {
+    pub(crate) fn new(receiver: mpsc::Receiver<()>) -> Self {
+        Self {
+            receiver,
+            started: Cell::new(false),
+        }
+    }
+
+    /// Blocks until the job has started
+    ///
+    /// Returns `false` if the job was dropped without running, e.g. because
+    /// the pool shut down.
+    pub fn wait(&self) -> bool {
+        if !self.started.get() && self.receiver.recv().is_ok() {
+            self.started.set(true);
+        }
+
+        self.started.get()
+    }
+
+    /// Returns whether the job has started, without blocking
+    pub fn has_started(&self) -> bool {
+        if !self.started.get() && self.receiver.try_recv().is_ok() {
+            self.started.set(true);
+        }
+
+        self.started.get()
+    }
+}
@@ -0,0 +1,25 @@
+/// Pins the current thread to the given CPU core
+///
+/// Failing to pin the thread, e.g. because the core doesn't exist, is ignored.
+#[cfg(target_os = "linux")]
+pub(crate) fn pin_current_thread(core: usize) {
+    let size = std::mem::size_of::<libc::cpu_set_t>();
+
+    // `CPU_SET` doesn't check whether the core fits into the set
+    if core >= 8 * size {
+        return;
+    }
+
+    // SAFETY: `set` is a valid, zeroed `cpu_set_t` which outlives the calls
+    unsafe {
+        let mut set: libc::cpu_set_t = std::mem::zeroed();
+
+        libc::CPU_ZERO(&mut set);
+        libc::CPU_SET(core, &mut set);
+        libc::sched_setaffinity(0, size, &set);
+    }
+}
+
+/// Pinning threads isn't supported on this platform, so this does nothing
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn pin_current_thread(_core: usize) {}
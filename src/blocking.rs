@@ -0,0 +1,191 @@
+use std::collections::VecDeque;
+use std::io;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError};
+use std::thread;
+use std::time::Duration;
+
+use crate::{Job, Shared, ThreadPoolBuilder};
+
+/// The maximum number of threads of the blocking lane, more jobs are queued
+const MAX_THREADS: usize = 512;
+
+/// How long an idle thread of the blocking lane waits for a job before it exits
+const KEEP_ALIVE: Duration = Duration::from_secs(10);
+
+/// A separate lane for blocking jobs, serviced by threads spawned on demand
+///
+/// Keeps long blocking jobs from occupying the workers, which stay free for
+/// short jobs.
+pub(crate) struct BlockingLane {
+    state: Mutex<LaneState>,
+
+    /// Notified whenever a job is pushed or the lane is closed
+    available: Condvar,
+
+    /// The threads are named `{prefix}-{index}`
+    prefix: String,
+
+    stack_size: Option<usize>,
+}
+
+/// An error returned by [`BlockingLane::push`]
+pub(crate) enum PushError {
+    /// The lane is closed
+    Closed,
+
+    /// There is no thread to run the job, and none could be spawned
+    SpawnFailed(io::Error),
+}
+
+struct LaneState {
+    jobs: VecDeque<Job>,
+
+    /// The handles of the spawned threads, some of which may have exited
+    threads: Vec<thread::JoinHandle<()>>,
+
+    /// The number of threads which haven't exited yet
+    live: usize,
+
+    /// The number of threads waiting for a job
+    idle: usize,
+
+    /// The index of the next spawned thread
+    next_index: usize,
+
+    /// Whether the lane has been closed, which rejects new jobs
+    closed: bool,
+}
+
+impl BlockingLane {
+    pub(crate) fn new(builder: &ThreadPoolBuilder) -> Self {
+        let prefix = match &builder.thread_name_prefix {
+            Some(prefix) => format!("{prefix}-blocking"),
+            None => "threatpool-blocking".into(),
+        };
+
+        Self {
+            state: Mutex::new(LaneState {
+                jobs: VecDeque::new(),
+                threads: Vec::new(),
+                live: 0,
+                idle: 0,
+                next_index: 0,
+                closed: false,
+            }),
+            available: Condvar::new(),
+            prefix,
+            stack_size: builder.stack_size,
+        }
+    }
+
+    /// Pushes a job, spawning a thread for it if every thread is busy
+    ///
+    /// If spawning the thread fails, the job waits for one of the existing
+    /// threads. Drops the job if the lane is closed, or if there is no thread
+    /// at all.
+    pub(crate) fn push(self: &Arc<Self>, shared: &Arc<Shared>, job: Job) -> Result<(), PushError> {
+        let mut state = self.lock();
+
+        if state.closed {
+            return Err(PushError::Closed);
+        }
+
+        state.jobs.push_back(job);
+
+        if state.jobs.len() > state.idle && state.live < MAX_THREADS {
+            if let Err(err) = self.spawn(&mut state, shared) {
+                if state.live == 0 {
+                    // Drop the job outside of the lock, since dropping it may run any code
+                    let job = state.jobs.pop_back();
+                    drop(state);
+                    drop(job);
+
+                    return Err(PushError::SpawnFailed(err));
+                }
+            }
+        } else {
+            self.available.notify_one();
+        }
+
+        Ok(())
+    }
+
+    /// Closes the lane, the threads exit once the queued jobs are done
+    pub(crate) fn close(&self) {
+        self.lock().closed = true;
+        self.available.notify_all();
+    }
+
+    /// Waits for every thread to exit
+    pub(crate) fn join(&self) {
+        for thread in self.take_threads() {
+            crate::join_thread(thread);
+        }
+    }
+
+    /// Takes the handles of the threads, which detaches them unless joined
+    pub(crate) fn take_threads(&self) -> Vec<thread::JoinHandle<()>> {
+        std::mem::take(&mut self.lock().threads)
+    }
+
+    /// Lets the threads exit in the background
+    pub(crate) fn detach(&self) {
+        self.lock().threads.clear();
+    }
+
+    fn spawn(self: &Arc<Self>, state: &mut LaneState, shared: &Arc<Shared>) -> io::Result<()> {
+        // Forget the handles of threads which have expired
+        state.threads.retain(|thread| !thread.is_finished());
+
+        let mut thread = thread::Builder::new().name(format!("{}-{}", self.prefix, state.next_index));
+
+        if let Some(stack_size) = self.stack_size {
+            thread = thread.stack_size(stack_size);
+        }
+
+        let lane = Arc::clone(self);
+        let shared = Arc::clone(shared);
+
+        let thread = thread.spawn(move || lane.run(&shared))?;
+
+        state.threads.push(thread);
+        state.live += 1;
+        state.next_index += 1;
+
+        Ok(())
+    }
+
+    fn run(&self, shared: &Shared) {
+        let mut state = self.lock();
+
+        loop {
+            if let Some(job) = state.jobs.pop_front() {
+                drop(state);
+                shared.run(job, None);
+                state = self.lock();
+
+                continue;
+            }
+
+            if state.closed {
+                break;
+            }
+
+            state.idle += 1;
+            let result = self.available.wait_timeout(state, KEEP_ALIVE);
+            let (guard, timeout) = result.unwrap_or_else(PoisonError::into_inner);
+            state = guard;
+            state.idle -= 1;
+
+            if timeout.timed_out() && state.jobs.is_empty() {
+                break;
+            }
+        }
+
+        state.live -= 1;
+    }
+
+    fn lock(&self) -> MutexGuard<'_, LaneState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
@@ -0,0 +1,780 @@
+use std::any::Any;
+use std::fmt;
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::Duration;
+
+use crate::{JobQueue, PoolEvent, Priority, ThreadPool, ThreadPoolError};
+
+/// A callback receiving the payload of a panicking job
+pub(crate) type PanicHandler = Arc<dyn Fn(Box<dyn Any + Send>) + Send + Sync>;
+
+/// A callback run by every worker before it starts executing jobs
+pub(crate) type WorkerInit = Arc<dyn Fn() + Send + Sync>;
+
+/// A callback run by a worker after every job
+pub(crate) type OnComplete = Arc<dyn Fn() + Send + Sync>;
+
+/// A callback receiving the running time of every job
+pub(crate) type TimingCallback = Arc<dyn Fn(Duration) + Send + Sync>;
+
+/// Creates the custom queue of a pool
+pub(crate) type JobQueueFactory = Arc<dyn Fn() -> Box<dyn JobQueue> + Send + Sync>;
+
+/// A function spawning a worker thread which runs the given closure
+pub(crate) type SpawnHandler =
+    Arc<dyn Fn(Box<dyn FnOnce() + Send>) -> thread::JoinHandle<()> + Send + Sync>;
+
+/// A callback receiving the name and running time of a slow job
+pub(crate) type SlowJobHandler = Arc<dyn Fn(Option<&str>, Duration) + Send + Sync>;
+
+/// The way jobs are distributed between the workers
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Scheduler {
+    /// All workers pop jobs from one shared queue
+    #[default]
+    GlobalQueue,
+
+    /// Every worker owns a local queue in addition to the shared one
+    ///
+    /// Jobs submitted from within a worker of the same pool are pushed onto
+    /// that worker's local queue, which it pops from first, newest job first.
+    /// Idle workers steal the oldest jobs of other local queues. This keeps
+    /// related jobs, e.g. a job and the jobs it spawns, on the same thread.
+    /// Every local queue has a lock of its own, so workers pushing and popping
+    /// their own jobs don't contend with each other for the shared queue.
+    ///
+    /// Priorities only apply to jobs in the shared queue, since jobs with a
+    /// priority other than 0 always go there. So do all jobs of a pool with a
+    /// [`ThreadPoolBuilder::queue_capacity`] or [`Dispatch::Synchronous`],
+    /// which only the shared queue can enforce.
+    WorkStealing,
+}
+
+/// What dropping the last handle to a [`ThreadPool`] does with the workers
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum DropBehavior {
+    /// Block until the workers have executed the queued jobs and exited,
+    /// like [`ThreadPool::shutdown`]
+    #[default]
+    Join,
+
+    /// Let the workers finish the queued jobs in the background, like
+    /// [`ThreadPool::shutdown_background`]
+    Detach,
+}
+
+/// The order in which queued jobs of the same priority are executed
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum QueueOrder {
+    /// The oldest job first
+    #[default]
+    Fifo,
+
+    /// The most recently submitted job first
+    ///
+    /// This finishes recently spawned work first, e.g. the children of a job
+    /// in a depth-first task tree, which keeps fewer jobs queued at once.
+    Lifo,
+}
+
+/// The way `execute` hands jobs to the workers
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Dispatch {
+    /// Jobs are queued according to the [`Scheduler`]
+    #[default]
+    Shared,
+
+    /// Every worker owns a queue of its own, which jobs are assigned to in turn
+    ///
+    /// This balances the number of jobs deterministically, even if some of
+    /// them take longer than others. A worker only runs the jobs assigned to
+    /// it, in the order they were submitted, so neither priorities nor the
+    /// [`Scheduler`] apply. The jobs of a removed worker are handed to the
+    /// remaining ones.
+    RoundRobin,
+
+    /// Submitting a job blocks until an idle worker is about to take it
+    ///
+    /// Jobs are never queued ahead of the workers, which gives the tightest
+    /// possible backpressure, so [`ThreadPoolBuilder::queue_capacity`] has no
+    /// effect. Otherwise jobs are handed out like with [`Dispatch::Shared`].
+    /// Submitting a job from within a job of the same pool deadlocks if no
+    /// other worker becomes idle.
+    Synchronous,
+}
+
+/// What [`ThreadPool::execute`] and [`ThreadPool::try_execute`] do with
+/// a job while the bounded queue is full
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum RejectionPolicy {
+    /// Block until a worker picks up a job
+    #[default]
+    Block,
+
+    /// Reject the job with [`ThreadPoolError::QueueFull`](crate::ThreadPoolError::QueueFull)
+    Abort,
+
+    /// Run the job on the submitting thread, which slows down the producer
+    CallerRuns,
+
+    /// Drop the oldest job of the lowest priority to make room
+    DiscardOldest,
+}
+
+/// A builder to configure a [`ThreadPool`]
+///
+/// # Example
+///
+/// ```
+/// use threatpool::ThreadPoolBuilder;
+///
+/// let pool = ThreadPoolBuilder::new()
+///     .num_threads(4)
+///     .thread_name_prefix("my-pool")
+///     .stack_size(8 * 1024 * 1024)
+///     .build();
+///
+/// pool.execute(|| {
+///     println!("Hello, World");
+/// });
+/// ```
+#[derive(Default, Clone)]
+pub struct ThreadPoolBuilder {
+    /// The number of workers, defaults to the number of available CPUs
+    pub(crate) num_threads: Option<usize>,
+
+    /// The prefix of the worker thread names, defaults to `threatpool-worker`
+    pub(crate) thread_name_prefix: Option<String>,
+
+    /// The stack size of the worker threads in bytes
+    pub(crate) stack_size: Option<usize>,
+
+    /// The capacity of the job queue, unbounded if `None`
+    pub(crate) queue_capacity: Option<usize>,
+
+    /// Creates the queue holding the shared jobs instead of the built-in one
+    pub(crate) job_queue: Option<JobQueueFactory>,
+
+    /// Called by a worker whenever a job panics
+    pub(crate) panic_handler: Option<PanicHandler>,
+
+    /// Run once by every worker on its own thread
+    pub(crate) worker_init: Option<WorkerInit>,
+
+    /// Spawns the worker threads instead of `thread::Builder::spawn`
+    pub(crate) spawn_handler: Option<SpawnHandler>,
+
+    /// Called by a worker after every job
+    pub(crate) on_complete: Option<OnComplete>,
+
+    /// Called by a worker with the running time of every job
+    pub(crate) timing_callback: Option<TimingCallback>,
+
+    pub(crate) scheduler: Scheduler,
+
+    pub(crate) order: QueueOrder,
+
+    pub(crate) dispatch: Dispatch,
+
+    pub(crate) rejection_policy: RejectionPolicy,
+
+    pub(crate) drop_behavior: DropBehavior,
+
+    /// How long an idle worker waits for a job before it exits
+    pub(crate) keep_alive: Option<Duration>,
+
+    /// The number of workers which never exit because of `keep_alive`, defaults to 1
+    pub(crate) min_threads: Option<usize>,
+
+    /// The maximum number of jobs started per second
+    pub(crate) rate_limit: Option<u32>,
+
+    /// The OS priority of the worker threads, left unchanged if `None`
+    pub(crate) thread_priority: Option<Priority>,
+
+    /// Receives a `PoolEvent` whenever a job is submitted, started or finished
+    pub(crate) event_sink: Option<mpsc::Sender<PoolEvent>>,
+
+    /// How long a job may run before it is reported as slow
+    pub(crate) slow_job_threshold: Option<Duration>,
+
+    /// Called by the watchdog for every slow job, which prints a warning if `None`
+    pub(crate) on_slow_job: Option<SlowJobHandler>,
+
+    /// The CPU cores the workers are pinned to, round-robin
+    #[cfg(feature = "affinity")]
+    pub(crate) cores: Option<Vec<usize>>,
+}
+
+impl ThreadPoolBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the number of workers, which must be greater than zero
+    pub fn num_threads(mut self, num_threads: usize) -> Self {
+        self.num_threads = Some(num_threads);
+        self
+    }
+
+    /// Sets the prefix of the worker thread names
+    ///
+    /// Workers are named `{prefix}-{index}`, e.g. `my-pool-0`.
+    pub fn thread_name_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.thread_name_prefix = Some(prefix.into());
+        self
+    }
+
+    /// Sets the stack size of the worker threads in bytes
+    pub fn stack_size(mut self, stack_size: usize) -> Self {
+        self.stack_size = Some(stack_size);
+        self
+    }
+
+    /// Bounds the job queue to the given number of jobs
+    ///
+    /// Once the queue is full `execute` blocks until a worker picks up a job,
+    /// while `try_execute_nonblocking` returns an error. The capacity must be
+    /// greater than zero.
+    pub fn queue_capacity(mut self, capacity: usize) -> Self {
+        self.queue_capacity = Some(capacity);
+        self
+    }
+
+    /// Sets a callback which a worker calls with the panic payload whenever a job panics
+    ///
+    /// The panic is caught either way, so the worker keeps running. The handler
+    /// runs on the worker, so [`current_job_name`](crate::current_job_name)
+    /// tells which named job panicked.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use threatpool::ThreadPoolBuilder;
+    ///
+    /// let pool = ThreadPoolBuilder::new()
+    ///     .panic_handler(|payload| {
+    ///         if let Some(message) = payload.downcast_ref::<&str>() {
+    ///             eprintln!("a job panicked: {message}");
+    ///         }
+    ///     })
+    ///     .build();
+    /// ```
+    pub fn panic_handler<F>(mut self, handler: F) -> Self
+    where
+        F: Fn(Box<dyn Any + Send>) + Send + Sync + 'static,
+    {
+        self.panic_handler = Some(Arc::new(handler));
+        self
+    }
+
+    /// Sets a callback which every worker runs once on its own thread before
+    /// executing any jobs
+    ///
+    /// This is the place to set up thread-locals which jobs reuse.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::cell::RefCell;
+    /// use threatpool::ThreadPoolBuilder;
+    ///
+    /// thread_local! {
+    ///     static BUFFER: RefCell<Vec<u8>> = RefCell::new(Vec::new());
+    /// }
+    ///
+    /// let pool = ThreadPoolBuilder::new()
+    ///     .worker_init(|| BUFFER.with(|buffer| buffer.borrow_mut().reserve(1024)))
+    ///     .build();
+    /// ```
+    pub fn worker_init<F>(mut self, init: F) -> Self
+    where
+        F: Fn() + Send + Sync + 'static,
+    {
+        self.worker_init = Some(Arc::new(init));
+        self
+    }
+
+    /// Sets a function which spawns the worker threads, e.g. to register them
+    /// with a profiler
+    ///
+    /// The function receives the main loop of a worker and must run it on a
+    /// new thread, whose handle it returns. The pool then no longer names the
+    /// threads or sets their stack size, the function can do so itself.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::thread;
+    /// use threatpool::ThreadPoolBuilder;
+    ///
+    /// let pool = ThreadPoolBuilder::new()
+    ///     .spawn_handler(|main| {
+    ///         println!("Spawning a worker");
+    ///         thread::Builder::new().name("custom".into()).spawn(main).unwrap()
+    ///     })
+    ///     .build();
+    /// ```
+    pub fn spawn_handler<F>(mut self, spawn_handler: F) -> Self
+    where
+        F: Fn(Box<dyn FnOnce() + Send>) -> thread::JoinHandle<()> + Send + Sync + 'static,
+    {
+        self.spawn_handler = Some(Arc::new(spawn_handler));
+        self
+    }
+
+    /// Sets a callback which a worker calls after every job, whether it
+    /// panicked or not
+    ///
+    /// The callback runs on the worker before the job counts as finished, so
+    /// it delays the next job and should be kept lightweight, e.g. ticking a
+    /// progress counter. A panicking callback is caught.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::sync::atomic::{AtomicUsize, Ordering};
+    /// use std::sync::Arc;
+    /// use threatpool::ThreadPoolBuilder;
+    ///
+    /// let done = Arc::new(AtomicUsize::new(0));
+    /// let counter = Arc::clone(&done);
+    ///
+    /// let pool = ThreadPoolBuilder::new()
+    ///     .on_complete(move || {
+    ///         counter.fetch_add(1, Ordering::Relaxed);
+    ///     })
+    ///     .build();
+    ///
+    /// pool.execute(|| println!("Hello, World"));
+    /// pool.join();
+    ///
+    /// assert_eq!(done.load(Ordering::Relaxed), 1);
+    /// ```
+    pub fn on_complete<F>(mut self, callback: F) -> Self
+    where
+        F: Fn() + Send + Sync + 'static,
+    {
+        self.on_complete = Some(Arc::new(callback));
+        self
+    }
+
+    /// Sets a callback which a worker calls with the running time of every job
+    ///
+    /// The time is measured around the job itself, including catching a panic,
+    /// and is only measured if a callback or an event sink is set. Like with
+    /// [`ThreadPoolBuilder::on_complete`], the callback delays the next job and
+    /// a panicking callback is caught.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::sync::{Arc, Mutex};
+    /// use threatpool::ThreadPoolBuilder;
+    ///
+    /// let durations = Arc::new(Mutex::new(Vec::new()));
+    /// let recorder = Arc::clone(&durations);
+    ///
+    /// let pool = ThreadPoolBuilder::new()
+    ///     .timing_callback(move |duration| recorder.lock().unwrap().push(duration))
+    ///     .build();
+    ///
+    /// pool.execute(|| println!("Hello, World"));
+    /// pool.join();
+    ///
+    /// assert_eq!(durations.lock().unwrap().len(), 1);
+    /// ```
+    pub fn timing_callback<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(Duration) + Send + Sync + 'static,
+    {
+        self.timing_callback = Some(Arc::new(callback));
+        self
+    }
+
+    /// Sets the way jobs are distributed between the workers, see [`Scheduler`]
+    pub fn scheduler(mut self, scheduler: Scheduler) -> Self {
+        self.scheduler = scheduler;
+        self
+    }
+
+    /// Sets a function creating a custom [`JobQueue`] which holds the queued jobs
+    ///
+    /// The custom queue decides the order of the jobs, so neither priorities
+    /// nor the [`QueueOrder`] apply, except for what the queue makes of
+    /// [`QueuedJob::priority`](crate::QueuedJob::priority). The capacity,
+    /// blocking and shutting down are still handled by the pool. The function
+    /// is called once per pool, e.g. again by [`ThreadPool::restart`]. With
+    /// [`Scheduler::WorkStealing`], the local queues of the workers stay
+    /// built-in.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::collections::VecDeque;
+    /// use threatpool::{JobQueue, QueuedJob, ThreadPoolBuilder};
+    ///
+    /// #[derive(Default)]
+    /// struct Fifo(VecDeque<QueuedJob>);
+    ///
+    /// impl JobQueue for Fifo {
+    ///     fn push(&mut self, job: QueuedJob) {
+    ///         self.0.push_back(job);
+    ///     }
+    ///
+    ///     fn pop(&mut self) -> Option<QueuedJob> {
+    ///         self.0.pop_front()
+    ///     }
+    /// }
+    ///
+    /// let pool = ThreadPoolBuilder::new()
+    ///     .job_queue(Fifo::default)
+    ///     .build();
+    /// ```
+    pub fn job_queue<F, Q>(mut self, make_queue: F) -> Self
+    where
+        F: Fn() -> Q + Send + Sync + 'static,
+        Q: JobQueue + 'static,
+    {
+        self.job_queue = Some(Arc::new(move || Box::new(make_queue())));
+        self
+    }
+
+    /// Sets the order in which queued jobs of the same priority are executed,
+    /// see [`QueueOrder`]
+    ///
+    /// Jobs with a higher priority are still executed first. With
+    /// [`Scheduler::WorkStealing`], workers always pop their local queue
+    /// newest first and steal oldest first.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use threatpool::{QueueOrder, ThreadPoolBuilder};
+    ///
+    /// let pool = ThreadPoolBuilder::new()
+    ///     .order(QueueOrder::Lifo)
+    ///     .build();
+    /// ```
+    pub fn order(mut self, order: QueueOrder) -> Self {
+        self.order = order;
+        self
+    }
+
+    /// Sets the way `execute` hands jobs to the workers, see [`Dispatch`]
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use threatpool::{Dispatch, ThreadPoolBuilder};
+    ///
+    /// let pool = ThreadPoolBuilder::new()
+    ///     .num_threads(4)
+    ///     .dispatch(Dispatch::RoundRobin)
+    ///     .build();
+    ///
+    /// for i in 0..8 {
+    ///     pool.execute(move || println!("job {i}"));
+    /// }
+    /// ```
+    pub fn dispatch(mut self, dispatch: Dispatch) -> Self {
+        self.dispatch = dispatch;
+        self
+    }
+
+    /// Sets what happens to a job submitted while the bounded queue is full,
+    /// see [`RejectionPolicy`]
+    ///
+    /// This applies to [`ThreadPool::execute`], [`ThreadPool::try_execute`],
+    /// [`ThreadPool::execute_named`], [`ThreadPool::execute_with_priority`] and
+    /// everything built on them, like [`ThreadPool::execute_with_result`] and
+    /// [`ThreadPool::scope`]. With [`RejectionPolicy::Abort`], `execute` and
+    /// [`Scope::spawn`](crate::Scope::spawn) panic.
+    ///
+    /// [`ThreadPool::execute_with_deadline`], [`ThreadPool::execute_sized`],
+    /// [`ThreadPool::execute_with_retry`], [`ThreadPool::execute_boxed`],
+    /// [`ThreadPool::execute_all`] and [`ThreadPool::execute_on`] ignore the
+    /// policy and keep blocking, as does the timer submitting the jobs of
+    /// [`ThreadPool::execute_after`] and [`ThreadPool::schedule_recurring`].
+    /// [`ThreadPool::broadcast`] queues its copies even if the queue is full.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use threatpool::{RejectionPolicy, ThreadPoolBuilder};
+    ///
+    /// let pool = ThreadPoolBuilder::new()
+    ///     .queue_capacity(64)
+    ///     .rejection_policy(RejectionPolicy::CallerRuns)
+    ///     .build();
+    /// ```
+    pub fn rejection_policy(mut self, policy: RejectionPolicy) -> Self {
+        self.rejection_policy = policy;
+        self
+    }
+
+    /// Sets whether dropping the last handle to the pool blocks until the
+    /// workers have exited, see [`DropBehavior`]
+    ///
+    /// Blocking in `drop` can be surprising, e.g. when a job never finishes.
+    /// Shutting the pool down explicitly isn't affected by this.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use threatpool::{DropBehavior, ThreadPoolBuilder};
+    ///
+    /// let pool = ThreadPoolBuilder::new()
+    ///     .drop_behavior(DropBehavior::Detach)
+    ///     .build();
+    ///
+    /// pool.execute(|| println!("Hello, World"));
+    ///
+    /// // Returns right away, the job still runs in the background
+    /// drop(pool);
+    /// ```
+    pub fn drop_behavior(mut self, behavior: DropBehavior) -> Self {
+        self.drop_behavior = behavior;
+        self
+    }
+
+    /// Lets workers exit after being idle for `keep_alive`, shrinking the pool
+    ///
+    /// The pool shrinks down to [`min_threads`](Self::min_threads) workers and
+    /// spawns replacements once there are more jobs than workers, up to the
+    /// configured number of threads. Idle workers expire one after another,
+    /// so shrinking by several workers takes a multiple of `keep_alive`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use threatpool::ThreadPoolBuilder;
+    ///
+    /// let pool = ThreadPoolBuilder::new()
+    ///     .num_threads(8)
+    ///     .min_threads(2)
+    ///     .keep_alive(Duration::from_secs(10))
+    ///     .build();
+    /// ```
+    pub fn keep_alive(mut self, keep_alive: Duration) -> Self {
+        self.keep_alive = Some(keep_alive);
+        self
+    }
+
+    /// Sets the number of workers a pool with a keep-alive never shrinks below
+    ///
+    /// Defaults to 1 and must be greater than zero.
+    pub fn min_threads(mut self, min_threads: usize) -> Self {
+        self.min_threads = Some(min_threads);
+        self
+    }
+
+    /// Limits the pool to starting `per_second` jobs per second
+    ///
+    /// Before running a job, a worker takes a token from a bucket which
+    /// refills at `per_second` tokens per second and holds at most that many,
+    /// blocking while it is empty. A burst of up to `per_second` jobs can start
+    /// at once. The limit must be greater than zero.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use threatpool::ThreadPoolBuilder;
+    ///
+    /// // Stay below the rate limit of some API
+    /// let pool = ThreadPoolBuilder::new()
+    ///     .rate_limit(10)
+    ///     .build();
+    /// ```
+    pub fn rate_limit(mut self, per_second: u32) -> Self {
+        self.rate_limit = Some(per_second);
+        self
+    }
+
+    /// Sets the OS scheduling priority of the worker threads
+    ///
+    /// Supported on Linux, where it sets the nice value of the threads, and on
+    /// Windows. Elsewhere this does nothing. Raising the priority usually
+    /// requires elevated privileges, without them it is left unchanged.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use threatpool::{Priority, ThreadPoolBuilder};
+    ///
+    /// let pool = ThreadPoolBuilder::new()
+    ///     .thread_priority(Priority::Low)
+    ///     .build();
+    /// ```
+    pub fn thread_priority(mut self, priority: Priority) -> Self {
+        self.thread_priority = Some(priority);
+        self
+    }
+
+    /// Sets a channel which receives a [`PoolEvent`] whenever a job is
+    /// submitted, started or finished
+    ///
+    /// The channel is unbounded, so a slow consumer never stalls the workers.
+    /// Events stop being sent once the receiver is dropped.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::sync::mpsc;
+    /// use threatpool::{PoolEvent, ThreadPoolBuilder};
+    ///
+    /// let (sender, receiver) = mpsc::channel();
+    /// let pool = ThreadPoolBuilder::new().event_sink(sender).build();
+    ///
+    /// pool.execute(|| println!("Hello, World"));
+    /// pool.join();
+    ///
+    /// for event in receiver.try_iter() {
+    ///     if let PoolEvent::JobFinished { duration, .. } = event {
+    ///         println!("A job took {duration:?}");
+    ///     }
+    /// }
+    /// ```
+    pub fn event_sink(mut self, sink: mpsc::Sender<PoolEvent>) -> Self {
+        self.event_sink = Some(sink);
+        self
+    }
+
+    /// Reports every job which runs for longer than `threshold`
+    ///
+    /// A watchdog thread checks the running jobs a few times per `threshold`
+    /// and reports each slow job once, through the callback set with
+    /// [`on_slow_job`](Self::on_slow_job) or with a warning printed to
+    /// stderr. Slow jobs aren't interrupted. The threshold must be greater
+    /// than zero.
+    pub fn slow_job_threshold(mut self, threshold: Duration) -> Self {
+        self.slow_job_threshold = Some(threshold);
+        self
+    }
+
+    /// Sets a callback which the watchdog calls with the name and running
+    /// time of every job exceeding the [`slow_job_threshold`](Self::slow_job_threshold)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use threatpool::ThreadPoolBuilder;
+    ///
+    /// let pool = ThreadPoolBuilder::new()
+    ///     .slow_job_threshold(Duration::from_secs(5))
+    ///     .on_slow_job(|name, elapsed| {
+    ///         eprintln!("{name:?} has been running for {elapsed:?}");
+    ///     })
+    ///     .build();
+    /// ```
+    pub fn on_slow_job<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(Option<&str>, Duration) + Send + Sync + 'static,
+    {
+        self.on_slow_job = Some(Arc::new(callback));
+        self
+    }
+
+    /// Pins every worker to one of the given CPU cores, assigned round-robin
+    /// by the worker index
+    ///
+    /// Only supported on Linux, elsewhere this does nothing. Cores which don't
+    /// exist are ignored, leaving the worker unpinned. Requires the
+    /// `affinity` feature.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use threatpool::ThreadPoolBuilder;
+    ///
+    /// let pool = ThreadPoolBuilder::new()
+    ///     .num_threads(4)
+    ///     .pin_to_cores(vec![0, 1])
+    ///     .build();
+    /// ```
+    #[cfg(feature = "affinity")]
+    pub fn pin_to_cores(mut self, cores: Vec<usize>) -> Self {
+        self.cores = Some(cores);
+        self
+    }
+
+    /// Builds the configured `ThreadPool`
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of threads, the minimum number of threads, the
+    /// queue capacity, the rate limit or the slow job threshold is zero, or
+    /// if a worker thread can't be spawned, see [`ThreadPoolBuilder::try_build`].
+    pub fn build(self) -> ThreadPool {
+        self.try_build().unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Builds the configured `ThreadPool`, returning an error if a worker
+    /// thread can't be spawned or if the number of threads is zero
+    ///
+    /// If spawning a worker or the watchdog of
+    /// [`ThreadPoolBuilder::slow_job_threshold`] fails, e.g. because of a limit
+    /// on the number of processes, the workers which were already spawned are
+    /// shut down and joined before the error is returned, so no threads are
+    /// leaked. The timer and blocking threads are spawned on first use
+    /// instead and panic if that fails, see [`ThreadPool::execute_after`] and
+    /// [`ThreadPool::execute_blocking`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the minimum number of threads, the queue capacity, the rate
+    /// limit or the slow job threshold is zero.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use threatpool::{ThreadPoolBuilder, ThreadPoolError};
+    ///
+    /// match ThreadPoolBuilder::new().num_threads(4).try_build() {
+    ///     Ok(pool) => pool.execute(|| println!("Hello, World")),
+    ///     Err(ThreadPoolError::SpawnFailed) => eprintln!("too many threads"),
+    ///     Err(err) => panic!("{err}"),
+    /// }
+    /// ```
+    pub fn try_build(self) -> Result<ThreadPool, ThreadPoolError> {
+        ThreadPool::from_builder(self)
+    }
+}
+
+impl fmt::Debug for ThreadPoolBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut debug = f.debug_struct("ThreadPoolBuilder");
+
+        debug
+            .field("num_threads", &self.num_threads)
+            .field("thread_name_prefix", &self.thread_name_prefix)
+            .field("stack_size", &self.stack_size)
+            .field("queue_capacity", &self.queue_capacity)
+            .field("job_queue", &self.job_queue.is_some())
+            .field("panic_handler", &self.panic_handler.is_some())
+            .field("worker_init", &self.worker_init.is_some())
+            .field("spawn_handler", &self.spawn_handler.is_some())
+            .field("on_complete", &self.on_complete.is_some())
+            .field("timing_callback", &self.timing_callback.is_some())
+            .field("scheduler", &self.scheduler)
+            .field("order", &self.order)
+            .field("dispatch", &self.dispatch)
+            .field("rejection_policy", &self.rejection_policy)
+            .field("drop_behavior", &self.drop_behavior)
+            .field("keep_alive", &self.keep_alive)
+            .field("min_threads", &self.min_threads)
+            .field("rate_limit", &self.rate_limit)
+            .field("thread_priority", &self.thread_priority)
+            .field("event_sink", &self.event_sink.is_some())
+            .field("slow_job_threshold", &self.slow_job_threshold)
+            .field("on_slow_job", &self.on_slow_job.is_some());
+
+        #[cfg(feature = "affinity")]
+        debug.field("cores", &self.cores);
+
+        debug.finish()
+    }
+}
@@ -0,0 +1,62 @@
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// A token to cancel a job submitted with
+/// [`ThreadPool::execute_cancellable`](crate::ThreadPool::execute_cancellable)
+/// or [`ThreadPool::execute_tracked`](crate::ThreadPool::execute_tracked)
+///
+/// Cancelling only prevents a job from starting, a job which is already
+/// running can't be interrupted.
+#[derive(Clone, Default)]
+pub struct CancelToken {
+    state: Arc<TokenState>,
+}
+
+#[derive(Default)]
+struct TokenState {
+    cancelled: AtomicBool,
+
+    /// Called by the first call to `cancel`
+    on_cancel: Mutex<Option<Box<dyn FnOnce() + Send>>>,
+}
+
+impl CancelToken {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets a callback which is called once the token is cancelled
+    pub(crate) fn on_cancel<F>(&self, f: F)
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        *self.state.on_cancel.lock().unwrap_or_else(PoisonError::into_inner) = Some(Box::new(f));
+    }
+
+    /// Cancels the job if it hasn't started yet
+    pub fn cancel(&self) {
+        if self.state.cancelled.swap(true, Ordering::SeqCst) {
+            return;
+        }
+
+        let on_cancel = self.state.on_cancel.lock().unwrap_or_else(PoisonError::into_inner).take();
+
+        if let Some(on_cancel) = on_cancel {
+            on_cancel();
+        }
+    }
+
+    /// Returns whether [`CancelToken::cancel`] has been called
+    pub fn is_cancelled(&self) -> bool {
+        self.state.cancelled.load(Ordering::SeqCst)
+    }
+}
+
+impl fmt::Debug for CancelToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CancelToken")
+            .field("cancelled", &self.is_cancelled())
+            .finish()
+    }
+}
@@ -0,0 +1,101 @@
+use std::sync::Arc;
+
+use crate::ThreadPool;
+
+/// A thread pool whose jobs get a reference to a shared context
+///
+/// See [`ThreadPool::with_context`]. The underlying pool is available
+/// through [`ContextPool::pool`] to join, inspect or shut it down.
+pub struct ContextPool<C> {
+    pool: ThreadPool,
+
+    context: Arc<C>,
+}
+
+impl<C> Clone for ContextPool<C> {
+    fn clone(&self) -> Self {
+        Self {
+            pool: self.pool.clone(),
+            context: Arc::clone(&self.context),
+        }
+    }
+}
+
+impl<C> ContextPool<C>
+where
+    C: Send + Sync + 'static,
+{
+    /// Executes a job which is passed a reference to the context
+    ///
+    /// # Panics
+    ///
+    /// Panics if the pool is no longer accepting jobs.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use threatpool::ThreadPool;
+    ///
+    /// let pool = ThreadPool::with_context(4, String::from("config"));
+    ///
+    /// pool.execute_ctx(|config| {
+    ///     assert_eq!(config, "config");
+    /// });
+    /// ```
+    pub fn execute_ctx<F>(&self, f: F)
+    where
+        F: FnOnce(&C) + Send + 'static,
+    {
+        let context = Arc::clone(&self.context);
+
+        self.pool.execute(move || f(&context));
+    }
+
+    /// Returns the context shared by the jobs
+    pub fn context(&self) -> &C {
+        &self.context
+    }
+
+    /// Returns the pool which runs the jobs
+    pub fn pool(&self) -> &ThreadPool {
+        &self.pool
+    }
+}
+
+impl ThreadPool {
+    /// Creates a new pool with `size` workers whose jobs share `context`
+    ///
+    /// Jobs submitted with [`ContextPool::execute_ctx`] are passed a reference
+    /// to the context, so they don't each need to close over an `Arc`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is zero.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::sync::atomic::{AtomicUsize, Ordering};
+    /// use threatpool::ThreadPool;
+    ///
+    /// let pool = ThreadPool::with_context(4, AtomicUsize::new(0));
+    ///
+    /// for _ in 0..8 {
+    ///     pool.execute_ctx(|counter| {
+    ///         counter.fetch_add(1, Ordering::SeqCst);
+    ///     });
+    /// }
+    ///
+    /// pool.pool().join();
+    /// assert_eq!(pool.context().load(Ordering::SeqCst), 8);
+    /// ```
+    pub fn with_context<C>(size: usize, context: C) -> ContextPool<C>
+    where
+        C: Send + Sync + 'static,
+    {
+        ContextPool {
+            pool: ThreadPool::new(size),
+            context: Arc::new(context),
+        }
+    }
+}
@@ -0,0 +1,145 @@
+use std::error::Error;
+use std::fmt;
+
+/// The reason a pool operation failed
+///
+/// The error types which hand a rejected job back convert into this, so
+/// `?` can be used to propagate all of them as one type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ThreadPoolError {
+    /// The bounded queue is full
+    QueueFull,
+
+    /// The pool is draining and doesn't accept jobs until it is reopened
+    Draining,
+
+    /// The pool has been shut down
+    PoolShutdown,
+
+    /// A pool was created without any workers
+    InvalidSize,
+
+    /// The operating system failed to spawn a worker or the watchdog thread
+    SpawnFailed,
+}
+
+impl fmt::Display for ThreadPoolError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::QueueFull => f.write_str("the job queue of the thread pool is full"),
+            Self::Draining => f.write_str("the thread pool is draining"),
+            Self::PoolShutdown => f.write_str("the thread pool has been shut down"),
+            Self::InvalidSize => f.write_str("a thread pool needs at least one worker"),
+            Self::SpawnFailed => f.write_str("failed to spawn a worker thread"),
+        }
+    }
+}
+
+impl Error for ThreadPoolError {}
+
+/// An error returned when a job couldn't be submitted to the pool
+///
+/// The rejected job is handed back so it can be retried or run elsewhere.
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub struct ExecuteError<F> {
+    job: F,
+
+    error: ThreadPoolError,
+}
+
+impl<F> ExecuteError<F> {
+    pub(crate) fn new(job: F, error: ThreadPoolError) -> Self {
+        Self { job, error }
+    }
+
+    /// Returns the job which couldn't be submitted
+    pub fn into_inner(self) -> F {
+        self.job
+    }
+
+    /// Returns why the job couldn't be submitted
+    pub fn error(&self) -> ThreadPoolError {
+        self.error
+    }
+}
+
+impl<F> fmt::Debug for ExecuteError<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ExecuteError")
+            .field("error", &self.error)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<F> fmt::Display for ExecuteError<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.error, f)
+    }
+}
+
+impl<F> Error for ExecuteError<F> {}
+
+impl<F> From<ExecuteError<F>> for ThreadPoolError {
+    fn from(err: ExecuteError<F>) -> Self {
+        err.error
+    }
+}
+
+/// An error returned by
+/// [`ThreadPool::try_execute_nonblocking`](crate::ThreadPool::try_execute_nonblocking)
+///
+/// All variants hand the rejected job back.
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub enum TryExecuteError<F> {
+    /// The bounded queue is full
+    Full(F),
+
+    /// The pool is draining and doesn't accept jobs until it is reopened
+    Draining(F),
+
+    /// The pool has been shut down
+    Disconnected(F),
+}
+
+impl<F> TryExecuteError<F> {
+    /// Returns the job which couldn't be submitted
+    pub fn into_inner(self) -> F {
+        match self {
+            Self::Full(f) | Self::Draining(f) | Self::Disconnected(f) => f,
+        }
+    }
+
+    /// Returns why the job couldn't be submitted
+    pub fn error(&self) -> ThreadPoolError {
+        match self {
+            Self::Full(_) => ThreadPoolError::QueueFull,
+            Self::Draining(_) => ThreadPoolError::Draining,
+            Self::Disconnected(_) => ThreadPoolError::PoolShutdown,
+        }
+    }
+}
+
+impl<F> fmt::Debug for TryExecuteError<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Full(_) => f.write_str("Full(..)"),
+            Self::Draining(_) => f.write_str("Draining(..)"),
+            Self::Disconnected(_) => f.write_str("Disconnected(..)"),
+        }
+    }
+}
+
+impl<F> fmt::Display for TryExecuteError<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.error(), f)
+    }
+}
+
+impl<F> Error for TryExecuteError<F> {}
+
+impl<F> From<TryExecuteError<F>> for ThreadPoolError {
+    fn from(err: TryExecuteError<F>) -> Self {
+        err.error()
+    }
+}
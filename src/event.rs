@@ -0,0 +1,29 @@
+use std::time::Duration;
+
+/// An event sent to the sink set with
+/// [`ThreadPoolBuilder::event_sink`](crate::ThreadPoolBuilder::event_sink)
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum PoolEvent {
+    /// A job has been submitted, sent right before it is queued
+    JobSubmitted,
+
+    /// A worker has started a job
+    JobStarted {
+        /// The name of the job, see [`ThreadPool::execute_named`](crate::ThreadPool::execute_named)
+        name: Option<String>,
+    },
+
+    /// A job has panicked, sent before its [`PoolEvent::JobFinished`]
+    JobPanicked {
+        name: Option<String>,
+    },
+
+    /// A job has finished, including one which panicked
+    JobFinished {
+        name: Option<String>,
+
+        /// How long the job ran
+        duration: Duration,
+    },
+}
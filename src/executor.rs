@@ -0,0 +1,40 @@
+use crate::{LogicalPool, ThreadPool};
+
+/// Something which executes jobs, so generic code doesn't depend on a concrete pool
+///
+/// Implemented by [`ThreadPool`], which includes the [global](crate::global)
+/// pool, and by [`LogicalPool`]. Tests can supply their own implementation,
+/// e.g. one which runs the job right away.
+///
+/// # Example
+///
+/// ```
+/// use threatpool::{Executor, ThreadPool};
+///
+/// fn greet(executor: &dyn Executor) {
+///     executor.spawn(Box::new(|| println!("Hello, World")));
+/// }
+///
+/// greet(&ThreadPool::new(4));
+/// greet(threatpool::global());
+/// ```
+pub trait Executor {
+    /// Executes a given job
+    ///
+    /// # Panics
+    ///
+    /// May panic if the executor is no longer accepting jobs.
+    fn spawn(&self, job: Box<dyn FnOnce() + Send + 'static>);
+}
+
+impl Executor for ThreadPool {
+    fn spawn(&self, job: Box<dyn FnOnce() + Send + 'static>) {
+        self.execute_boxed(job);
+    }
+}
+
+impl Executor for LogicalPool {
+    fn spawn(&self, job: Box<dyn FnOnce() + Send + 'static>) {
+        self.execute(job);
+    }
+}
@@ -0,0 +1,108 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{mpsc, Arc, Mutex, PoisonError};
+use std::task::{Context, Poll, Waker};
+
+use crate::ThreadPool;
+
+/// A future resolving to the result of a job submitted with
+/// [`ThreadPool::spawn_blocking`]
+///
+/// Dropping a `BlockingFuture` doesn't cancel the job, its result is simply
+/// discarded.
+pub struct BlockingFuture<T> {
+    /// Receives the value produced by the job
+    receiver: mpsc::Receiver<T>,
+
+    /// The waker of the last poll, woken by the job once it has sent its value
+    waker: Arc<Mutex<Option<Waker>>>,
+}
+
+impl<T> Future for BlockingFuture<T> {
+    type Output = T;
+
+    /// # Panics
+    ///
+    /// Panics if the job panicked.
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<T> {
+        if let Some(value) = self.try_recv() {
+            return Poll::Ready(value);
+        }
+
+        *self.waker.lock().unwrap_or_else(PoisonError::into_inner) = Some(cx.waker().clone());
+
+        // The job may have sent its value before the waker was stored
+        match self.try_recv() {
+            Some(value) => Poll::Ready(value),
+            None => Poll::Pending,
+        }
+    }
+}
+
+impl<T> BlockingFuture<T> {
+    fn try_recv(&self) -> Option<T> {
+        match self.receiver.try_recv() {
+            Ok(value) => Some(value),
+            Err(mpsc::TryRecvError::Empty) => None,
+            Err(mpsc::TryRecvError::Disconnected) => panic!("a blocking job panicked"),
+        }
+    }
+}
+
+/// Wakes the stored waker when dropped, even if the job panics
+struct WakeOnDrop(Arc<Mutex<Option<Waker>>>);
+
+impl Drop for WakeOnDrop {
+    fn drop(&mut self) {
+        if let Some(waker) = self.0.lock().unwrap_or_else(PoisonError::into_inner).take() {
+            waker.wake();
+        }
+    }
+}
+
+impl ThreadPool {
+    /// Executes a given job and returns a future resolving to its result
+    ///
+    /// This moves blocking or CPU-bound work off an async runtime, the future
+    /// can be awaited from any executor.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the pool is no longer accepting jobs. Polling the future
+    /// panics if the job panicked.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use threatpool::ThreadPool;
+    ///
+    /// let pool = ThreadPool::new(4);
+    ///
+    /// let result = futures::executor::block_on(async {
+    ///     pool.spawn_blocking(|| 6 * 7).await
+    /// });
+    ///
+    /// assert_eq!(result, 42);
+    /// ```
+    pub fn spawn_blocking<F, T>(&self, f: F) -> BlockingFuture<T>
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        let (sender, receiver) = mpsc::channel();
+        let waker: Arc<Mutex<Option<Waker>>> = Arc::new(Mutex::new(None));
+
+        let job_waker = Arc::clone(&waker);
+
+        self.execute(move || {
+            // Locals are dropped in reverse order, so the future is woken after
+            // the value has been sent or the sender has been dropped by a panic
+            let _wake = WakeOnDrop(job_waker);
+            let sender = sender;
+
+            let _ = sender.send(f());
+        });
+
+        BlockingFuture { receiver, waker }
+    }
+}
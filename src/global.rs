@@ -0,0 +1,38 @@
+use std::sync::OnceLock;
+
+use crate::ThreadPool;
+
+static GLOBAL: OnceLock<ThreadPool> = OnceLock::new();
+
+/// Returns the global pool, creating it on first access
+///
+/// The global pool has one worker per available CPU and lives until the
+/// program exits, so it is never shut down. Since it is shared by everyone,
+/// it can't be configured or resized; create your own [`ThreadPool`] if you
+/// need control over it.
+///
+/// # Example
+///
+/// ```
+/// let handle = threatpool::global().execute_with_result(|| 6 * 7);
+/// assert_eq!(handle.join().unwrap(), 42);
+/// ```
+pub fn global() -> &'static ThreadPool {
+    GLOBAL.get_or_init(ThreadPool::default)
+}
+
+/// Executes a job on the [global](global) pool
+///
+/// # Example
+///
+/// ```
+/// threatpool::spawn(|| {
+///     println!("Hello, World");
+/// });
+/// ```
+pub fn spawn<F>(f: F)
+where
+    F: FnOnce() + Send + 'static,
+{
+    global().execute(f);
+}
@@ -0,0 +1,241 @@
+use std::any::Any;
+use std::cell::RefCell;
+use std::error::Error;
+use std::fmt;
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+/// What a job sends to its handle, the timing is missing if it never ran
+pub(crate) type Outcome<T> = (Result<T, JobError>, Option<JobTiming>);
+
+/// A handle to the result of a job submitted with
+/// [`ThreadPool::execute_with_result`](crate::ThreadPool::execute_with_result)
+/// or [`ThreadPool::execute_tracked`](crate::ThreadPool::execute_tracked)
+///
+/// Dropping a `JobHandle` without joining it is fine, the job still runs
+/// and its result is simply discarded.
+pub struct JobHandle<T> {
+    /// Receives the value produced by the job, or why it didn't produce one
+    receiver: mpsc::Receiver<Outcome<T>>,
+
+    /// The outcome if it has already been received by `timing`
+    outcome: RefCell<Option<Outcome<T>>>,
+}
+
+/// When a job was submitted, started and finished, see [`JobHandle::timing`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JobTiming {
+    /// When the job was submitted to the pool
+    pub submitted: Instant,
+
+    /// When a worker started running the job
+    pub started: Instant,
+
+    /// When the job returned or panicked
+    pub finished: Instant,
+}
+
+impl JobTiming {
+    /// Returns how long the job waited in the queue
+    pub fn queue_time(&self) -> Duration {
+        self.started - self.submitted
+    }
+
+    /// Returns how long the job ran
+    pub fn run_time(&self) -> Duration {
+        self.finished - self.started
+    }
+}
+
+impl<T> JobHandle<T> {
+    pub(crate) fn new(receiver: mpsc::Receiver<Outcome<T>>) -> Self {
+        Self {
+            receiver,
+            outcome: RefCell::new(None),
+        }
+    }
+
+    /// Returns when the job was submitted, started and finished
+    ///
+    /// Returns `None` without blocking if the job hasn't finished yet, or if
+    /// it never ran, e.g. because it was cancelled. The result can still be
+    /// joined afterwards.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use threatpool::ThreadPool;
+    ///
+    /// let pool = ThreadPool::new(4);
+    ///
+    /// let handle = pool.execute_with_result(|| 6 * 7);
+    /// pool.join();
+    ///
+    /// let timing = handle.timing().unwrap();
+    /// println!("waited {:?}, ran {:?}", timing.queue_time(), timing.run_time());
+    /// ```
+    pub fn timing(&self) -> Option<JobTiming> {
+        let mut outcome = self.outcome.borrow_mut();
+
+        if outcome.is_none() {
+            *outcome = self.receiver.try_recv().ok();
+        }
+
+        outcome.as_ref().and_then(|(_, timing)| *timing)
+    }
+
+    /// Takes the outcome received by `timing`, if there is one
+    fn take_outcome(&self) -> Option<Result<T, JobError>> {
+        self.outcome.borrow_mut().take().map(|(result, _)| result)
+    }
+
+    /// Blocks until the job has finished and returns its result
+    ///
+    /// Returns an error if the job panicked, or if it was cancelled or
+    /// dropped before it could produce a value. Like with
+    /// [`std::thread::JoinHandle::join`], the panic can be raised again with
+    /// [`std::panic::resume_unwind`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use threatpool::ThreadPool;
+    ///
+    /// let pool = ThreadPool::new(4);
+    ///
+    /// let handle = pool.execute_with_result(|| 6 * 7);
+    /// assert_eq!(handle.join().unwrap(), 42);
+    /// ```
+    pub fn join(self) -> Result<T, JobError> {
+        if let Some(result) = self.take_outcome() {
+            return result;
+        }
+
+        match self.receiver.recv() {
+            Ok((result, _)) => result,
+            Err(mpsc::RecvError) => Err(JobError::Disconnected),
+        }
+    }
+
+    /// Blocks until the job has finished or `timeout` has passed
+    ///
+    /// The timeout only bounds how long the caller waits, a running job can't
+    /// be stopped. If the job finishes after the timeout, its result is discarded.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use threatpool::ThreadPool;
+    ///
+    /// let pool = ThreadPool::new(4);
+    ///
+    /// let handle = pool.execute_with_result(|| 6 * 7);
+    /// assert_eq!(handle.join_timeout(Duration::from_secs(1)).unwrap(), 42);
+    /// ```
+    pub fn join_timeout(self, timeout: Duration) -> Result<T, JoinTimeout> {
+        if let Some(result) = self.take_outcome() {
+            return result.map_err(JoinTimeout::from);
+        }
+
+        match self.receiver.recv_timeout(timeout) {
+            Ok((result, _)) => result.map_err(JoinTimeout::from),
+            Err(mpsc::RecvTimeoutError::Timeout) => Err(JoinTimeout::Timeout),
+            Err(mpsc::RecvTimeoutError::Disconnected) => Err(JoinTimeout::Disconnected),
+        }
+    }
+}
+
+/// Blocks until every job has finished and returns their results in the
+/// order of the handles
+///
+/// # Panics
+///
+/// Panics if any of the jobs failed to produce a value, once all of them are
+/// done. Join the handles one by one to handle failed jobs instead.
+///
+/// # Example
+///
+/// ```
+/// use threatpool::ThreadPool;
+///
+/// let pool = ThreadPool::new(4);
+///
+/// let handles = (0..4).map(|i| pool.execute_with_result(move || i * 2)).collect();
+/// assert_eq!(threatpool::join_all(handles), [0, 2, 4, 6]);
+/// ```
+pub fn join_all<T>(handles: Vec<JobHandle<T>>) -> Vec<T> {
+    // Join everything before panicking, so no job outlives the call
+    let results: Vec<_> = handles.into_iter().map(JobHandle::join).collect();
+
+    results
+        .into_iter()
+        .map(|result| result.unwrap_or_else(|err| panic!("a joined job failed: {err}")))
+        .collect()
+}
+
+/// An error returned by [`JobHandle::join`]
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum JobError {
+    /// The job panicked with the given payload
+    Panic(Box<dyn Any + Send + 'static>),
+
+    /// The job was cancelled before it started
+    Cancelled,
+
+    /// The job was dropped before it could produce a value
+    Disconnected,
+}
+
+impl fmt::Display for JobError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Panic(_) => f.write_str("the job panicked"),
+            Self::Cancelled => f.write_str("the job was cancelled before it started"),
+            Self::Disconnected => f.write_str("the job was dropped before it produced a value"),
+        }
+    }
+}
+
+impl Error for JobError {}
+
+/// An error returned by [`JobHandle::join_timeout`]
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum JoinTimeout {
+    /// The job didn't finish in time
+    Timeout,
+
+    /// The job panicked with the given payload
+    Panic(Box<dyn Any + Send + 'static>),
+
+    /// The job was cancelled before it started
+    Cancelled,
+
+    /// The job was dropped before it could produce a value
+    Disconnected,
+}
+
+impl From<JobError> for JoinTimeout {
+    fn from(err: JobError) -> Self {
+        match err {
+            JobError::Panic(payload) => Self::Panic(payload),
+            JobError::Cancelled => Self::Cancelled,
+            JobError::Disconnected => Self::Disconnected,
+        }
+    }
+}
+
+impl fmt::Display for JoinTimeout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Timeout => f.write_str("timed out waiting for the job"),
+            Self::Panic(_) => f.write_str("the job panicked"),
+            Self::Cancelled => JobError::Cancelled.fmt(f),
+            Self::Disconnected => JobError::Disconnected.fmt(f),
+        }
+    }
+}
+
+impl Error for JoinTimeout {}
@@ -0,0 +1,82 @@
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
+
+/// A snapshot of what a worker is doing, see [`ThreadPool::health`](crate::ThreadPool::health)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorkerHealth {
+    /// The index of the worker, which is also part of its thread name
+    pub index: usize,
+
+    /// How long the current job has been running, `None` if the worker is idle
+    pub running_for: Option<Duration>,
+
+    /// The name of the current job, if it was submitted with
+    /// [`ThreadPool::execute_named`](crate::ThreadPool::execute_named)
+    pub job_name: Option<String>,
+}
+
+impl WorkerHealth {
+    /// Returns whether the worker is waiting for a job
+    pub fn is_idle(&self) -> bool {
+        self.running_for.is_none()
+    }
+}
+
+/// The job a worker is currently running, updated by the worker itself
+#[derive(Default)]
+pub(crate) struct WorkerSlot {
+    current: Mutex<Option<Current>>,
+}
+
+struct Current {
+    started: Instant,
+
+    name: Option<String>,
+
+    /// Whether the watchdog has already reported the job as slow
+    reported: bool,
+}
+
+impl WorkerSlot {
+    pub(crate) fn start(&self, name: Option<String>) {
+        *self.lock() = Some(Current {
+            started: Instant::now(),
+            name,
+            reported: false,
+        });
+    }
+
+    pub(crate) fn finish(&self) {
+        *self.lock() = None;
+    }
+
+    pub(crate) fn health(&self, index: usize) -> WorkerHealth {
+        let current = self.lock();
+
+        WorkerHealth {
+            index,
+            running_for: current.as_ref().map(|current| current.started.elapsed()),
+            job_name: current.as_ref().and_then(|current| current.name.clone()),
+        }
+    }
+
+    /// Returns the name and running time of the current job if it has been
+    /// running for longer than `threshold`, but only the first time
+    pub(crate) fn take_slow(&self, threshold: Duration) -> Option<(Option<String>, Duration)> {
+        let mut current = self.lock();
+        let current = current.as_mut().filter(|current| !current.reported)?;
+
+        let elapsed = current.started.elapsed();
+
+        if elapsed <= threshold {
+            return None;
+        }
+
+        current.reported = true;
+        Some((current.name.clone(), elapsed))
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Option<Current>> {
+        self.current.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
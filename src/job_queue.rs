@@ -0,0 +1,60 @@
+use crate::Job;
+
+/// A custom data structure holding the queued jobs, see
+/// [`ThreadPoolBuilder::job_queue`](crate::ThreadPoolBuilder::job_queue)
+///
+/// The pool calls the methods while holding the lock of its queue, so an
+/// implementation doesn't need any synchronization of its own. The pool
+/// also keeps track of the number of jobs, the capacity and shutting down,
+/// and wakes up the workers, so `pop` must never block. Every pushed job has
+/// to be popped exactly once, in whichever order the queue chooses.
+///
+/// # Example
+///
+/// ```
+/// use threatpool::{JobQueue, QueuedJob};
+///
+/// /// Runs the most recently submitted job first
+/// #[derive(Default)]
+/// struct Stack(Vec<QueuedJob>);
+///
+/// impl JobQueue for Stack {
+///     fn push(&mut self, job: QueuedJob) {
+///         self.0.push(job);
+///     }
+///
+///     fn pop(&mut self) -> Option<QueuedJob> {
+///         self.0.pop()
+///     }
+/// }
+/// ```
+pub trait JobQueue: Send {
+    /// Adds a job to the queue
+    fn push(&mut self, job: QueuedJob);
+
+    /// Removes the job which should run next, `None` if the queue is empty
+    fn pop(&mut self) -> Option<QueuedJob>;
+}
+
+/// A job held by a [`JobQueue`]
+pub struct QueuedJob {
+    pub(crate) job: Job,
+
+    priority: u8,
+}
+
+impl QueuedJob {
+    pub(crate) fn new(job: Job, priority: u8) -> Self {
+        Self { job, priority }
+    }
+
+    /// Returns the priority the job was submitted with, 0 for plain jobs
+    pub fn priority(&self) -> u8 {
+        self.priority
+    }
+
+    /// Returns the name given to [`ThreadPool::execute_named`](crate::ThreadPool::execute_named)
+    pub fn name(&self) -> Option<&str> {
+        self.job.name.as_deref()
+    }
+}
@@ -0,0 +1,161 @@
+use std::sync::mpsc;
+
+use crate::{catch_result, JobError, ThreadPool};
+
+/// A group of jobs whose results are collected in the order they finish
+///
+/// Unlike a bunch of [`JobHandle`](crate::JobHandle)s, a `JoinSet` keeps
+/// track of how many jobs are outstanding, so [`JoinSet::join_next`] knows
+/// when every result has been collected.
+///
+/// # Example
+///
+/// ```
+/// use threatpool::{JoinSet, ThreadPool};
+///
+/// let pool = ThreadPool::new(4);
+/// let mut set = JoinSet::new(&pool);
+///
+/// for i in 0..4 {
+///     set.spawn(move || i * 2);
+/// }
+///
+/// while let Some(result) = set.join_next() {
+///     println!("{}", result.unwrap());
+/// }
+/// ```
+pub struct JoinSet<T> {
+    pool: ThreadPool,
+
+    sender: mpsc::Sender<Result<T, JobError>>,
+
+    receiver: mpsc::Receiver<Result<T, JobError>>,
+
+    /// The number of jobs whose result hasn't been collected yet
+    outstanding: usize,
+}
+
+/// Sends the result of a job, or [`JobError::Disconnected`] if the job is
+/// dropped without running, so the set never waits for it forever
+struct Reply<T> {
+    sender: Option<mpsc::Sender<Result<T, JobError>>>,
+}
+
+impl<T> Reply<T> {
+    fn send(mut self, result: Result<T, JobError>) {
+        if let Some(sender) = self.sender.take() {
+            // The set may have been dropped, in which case nobody wants the result
+            let _ = sender.send(result);
+        }
+    }
+}
+
+impl<T> Drop for Reply<T> {
+    fn drop(&mut self) {
+        if let Some(sender) = self.sender.take() {
+            let _ = sender.send(Err(JobError::Disconnected));
+        }
+    }
+}
+
+impl<T> JoinSet<T>
+where
+    T: Send + 'static,
+{
+    /// Creates an empty set whose jobs are executed on `pool`
+    pub fn new(pool: &ThreadPool) -> Self {
+        let (sender, receiver) = mpsc::channel();
+
+        Self {
+            pool: pool.clone(),
+            sender,
+            receiver,
+            outstanding: 0,
+        }
+    }
+
+    /// Executes a given job and adds it to the set
+    ///
+    /// If the job panics, the panic is passed to the set as [`JobError::Panic`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the pool is no longer accepting jobs.
+    pub fn spawn<F>(&mut self, f: F)
+    where
+        F: FnOnce() -> T + Send + 'static,
+    {
+        let reply = Reply {
+            sender: Some(self.sender.clone()),
+        };
+
+        self.pool.execute(move || reply.send(catch_result(f)));
+        self.outstanding += 1;
+    }
+
+    /// Blocks until the next job has finished and returns its result, or
+    /// returns `None` once every result has been collected
+    pub fn join_next(&mut self) -> Option<Result<T, JobError>> {
+        if self.outstanding == 0 {
+            return None;
+        }
+
+        // The set holds a sender itself, so receiving can't fail
+        let result = self.receiver.recv().unwrap_or(Err(JobError::Disconnected));
+        self.outstanding -= 1;
+
+        Some(result)
+    }
+
+    /// Blocks until every job has finished and returns their results in the
+    /// order they finished in
+    pub fn join_all(mut self) -> Vec<Result<T, JobError>> {
+        std::iter::from_fn(|| self.join_next()).collect()
+    }
+
+    /// Returns the number of jobs whose result hasn't been collected yet
+    pub fn len(&self) -> usize {
+        self.outstanding
+    }
+
+    /// Returns whether every result has been collected
+    pub fn is_empty(&self) -> bool {
+        self.outstanding == 0
+    }
+}
+
+impl ThreadPool {
+    /// Executes every job and returns a [`JoinSet`] collecting their results
+    ///
+    /// # Panics
+    ///
+    /// Panics if the pool is no longer accepting jobs.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use threatpool::ThreadPool;
+    ///
+    /// let pool = ThreadPool::new(4);
+    ///
+    /// let set = pool.spawn_many((0..4).map(|i| move || i * 2));
+    ///
+    /// let mut results: Vec<_> = set.join_all().into_iter().map(Result::unwrap).collect();
+    /// results.sort();
+    /// assert_eq!(results, [0, 2, 4, 6]);
+    /// ```
+    pub fn spawn_many<I, F, T>(&self, jobs: I) -> JoinSet<T>
+    where
+        I: IntoIterator<Item = F>,
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        let mut set = JoinSet::new(self);
+
+        for job in jobs {
+            set.spawn(job);
+        }
+
+        set
+    }
+}
@@ -13,40 +13,708 @@
 //! });
 //! ```
 
+#[cfg(feature = "affinity")]
+mod affinity;
+mod ack;
+mod blocking;
+mod builder;
+mod cancel;
+mod context;
+mod error;
+mod event;
+mod executor;
+mod future;
+mod global;
+mod handle;
+mod job_queue;
+mod join_set;
+mod health;
+mod parallel;
+mod priority;
+mod queue;
+mod rate;
+mod recurring;
+mod registry;
+mod scope;
+mod state;
+mod stats;
+mod timer;
+mod watchdog;
+
+pub use ack::AckReceiver;
+pub use builder::{
+    Dispatch, DropBehavior, QueueOrder, RejectionPolicy, Scheduler, ThreadPoolBuilder,
+};
+pub use cancel::CancelToken;
+pub use context::ContextPool;
+pub use error::{ExecuteError, ThreadPoolError, TryExecuteError};
+pub use event::PoolEvent;
+pub use executor::Executor;
+pub use future::BlockingFuture;
+pub use global::{global, spawn};
+pub use handle::{join_all, JobError, JobHandle, JobTiming, JoinTimeout};
+pub use health::WorkerHealth;
+pub use job_queue::{JobQueue, QueuedJob};
+pub use join_set::JoinSet;
+pub use priority::Priority;
+pub use recurring::RecurringHandle;
+pub use registry::{LogicalPool, PoolRegistry};
+pub use scope::Scope;
+pub use state::PoolState;
+pub use stats::PoolStats;
+
+use std::cell::{Cell, RefCell};
+use std::io;
+use std::mem;
 use std::thread;
-use std::sync::{mpsc, Arc, Mutex};
+use std::time::{Duration, Instant};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicU64, AtomicU8, AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc, Condvar, Mutex, MutexGuard, PoisonError, Weak};
+
+use blocking::{BlockingLane, PushError};
+use builder::{OnComplete, PanicHandler, TimingCallback};
+use handle::Outcome;
+use health::WorkerSlot;
+use queue::{Exit, Queue, Target, TryPushError};
+use rate::RateLimiter;
+use recurring::Recurring;
+use timer::Timer;
+
+/// The id of the next created pool
+static NEXT_POOL_ID: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+    /// The id of the pool and the index of the worker running on this thread
+    static CURRENT_WORKER: Cell<Option<(usize, usize)>> = const { Cell::new(None) };
+
+    /// The name of the job running on this thread
+    static CURRENT_JOB_NAME: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// Returns the name of the job running on the current thread, if it was
+/// submitted with [`ThreadPool::execute_named`]
+///
+/// The name stays available to the panic handler of a panicking job.
+///
+/// # Example
+///
+/// ```
+/// use threatpool::ThreadPool;
+///
+/// let pool = ThreadPool::new(4);
+///
+/// pool.execute_named("greeter", || {
+///     assert_eq!(threatpool::current_job_name().as_deref(), Some("greeter"));
+/// });
+/// ```
+pub fn current_job_name() -> Option<String> {
+    CURRENT_JOB_NAME.with(|name| name.borrow().clone())
+}
 
+/// A pool of worker threads
+///
+/// Cloning a `ThreadPool` is cheap and yields another handle to the same
+/// workers. The pool shuts down once the last handle is dropped.
+#[derive(Clone)]
 pub struct ThreadPool {
+    inner: Arc<Inner>,
+}
+
+/// The state shared by all clones of a `ThreadPool`
+struct Inner {
     /// A `Vec` of workers which execute the jobs
-    workers: Vec<Worker>,
+    workers: Mutex<Vec<Worker>>,
+
+    shared: Arc<Shared>,
+
+    /// The timer for delayed jobs, spawned on first use
+    timer: Mutex<Option<Timer>>,
+
+    /// Runs the jobs submitted with `execute_blocking`
+    blocking: Arc<BlockingLane>,
+
+    /// The configuration used to spawn additional workers
+    builder: ThreadPoolBuilder,
+
+    /// The index of the next spawned worker
+    next_index: AtomicUsize,
+
+    /// The number of workers a pool with a keep-alive grows back to when it's busy
+    size: AtomicUsize,
+}
+
+/// The state shared between the pool and its workers
+struct Shared {
+    /// Identifies the pool in `CURRENT_WORKER`
+    id: usize,
+
+    queue: Queue,
+
+    scheduler: Scheduler,
+
+    /// The current `PoolState` as its discriminant
+    state: AtomicU8,
+
+    /// The number of jobs which are queued or running
+    pending: AtomicUsize,
+
+    /// The number of jobs waiting in the queue
+    queued: AtomicUsize,
+
+    /// The number of jobs currently executing
+    active: AtomicUsize,
+
+    /// The number of jobs which have finished
+    completed: AtomicU64,
+
+    /// The number of jobs which have been queued
+    submitted: AtomicU64,
+
+    /// The number of jobs which panicked
+    failed: AtomicU64,
+
+    /// The summed size of the queued closures given to `execute_sized`
+    queued_bytes: AtomicUsize,
+
+    /// The number of workers waiting for a job
+    idle_workers: AtomicUsize,
+
+    /// Guards waiting on `idle`
+    lock: Mutex<()>,
+
+    /// Notified whenever `pending` drops to zero
+    idle: Condvar,
+
+    panic_handler: Option<PanicHandler>,
+
+    on_complete: Option<OnComplete>,
+
+    timing_callback: Option<TimingCallback>,
+
+    /// Limits how many jobs are started per second
+    rate_limiter: Option<RateLimiter>,
+
+    /// Receives a `PoolEvent` whenever a job is submitted, started or finished
+    event_sink: Option<mpsc::Sender<PoolEvent>>,
+
+    /// The indices of the workers which have exited
+    exited: Mutex<Vec<usize>>,
+
+    /// Notified whenever a worker exits
+    worker_exited: Condvar,
+
+    /// The indices of the workers which have exited after idling for the keep-alive
+    expired: Mutex<Vec<usize>>,
+}
+
+impl Shared {
+    /// Pushes a job onto the queue, handing it back if the queue is closed
+    fn push(&self, priority: u8, job: Job) -> Result<(), Job> {
+        self.push_to(self.target(priority), job)
+    }
+
+    /// Pushes a job onto the given queue, blocking while the queue is full
+    fn push_to(&self, target: Target, job: Job) -> Result<(), Job> {
+        self.add_jobs(1);
+
+        self.queue.push(target, job).map(|()| self.accept_jobs(1)).map_err(|job| {
+            self.reject_jobs(1);
+            job
+        })
+    }
+
+    /// Pushes a job onto the queue without blocking
+    fn try_push(&self, priority: u8, job: Job) -> Result<(), TryPushError> {
+        self.add_jobs(1);
+
+        self.queue.try_push(self.target(priority), job).map(|()| self.accept_jobs(1)).map_err(|err| {
+            self.reject_jobs(1);
+            err
+        })
+    }
+
+    /// Pushes a job onto the queue without blocking, even if the queue is
+    /// full, see [`Queue::push_unbounded`]
+    fn push_unbounded(&self, priority: u8, job: Job) -> Result<(), Job> {
+        self.add_jobs(1);
+
+        self.queue.push_unbounded(self.target(priority), job).map(|()| self.accept_jobs(1)).map_err(|job| {
+            self.reject_jobs(1);
+            job
+        })
+    }
+
+    /// Pushes a job onto the queue without blocking, dropping the oldest job
+    /// if the queue is full, see [`Queue::push_displacing`]
+    fn push_displacing(&self, priority: u8, job: Job) -> Result<(), Job> {
+        self.add_jobs(1);
+
+        match self.queue.push_displacing(self.target(priority), job) {
+            Ok(displaced) => {
+                self.accept_jobs(1);
+
+                if let Some(displaced) = displaced {
+                    // The displaced job is dropped without running, like a stale one
+                    self.unqueue_bytes(&displaced);
+                    self.reject_jobs(1);
+                }
+
+                Ok(())
+            }
+
+            Err(job) => {
+                self.reject_jobs(1);
+                Err(job)
+            }
+        }
+    }
+
+    /// Runs a job on the current thread instead of queueing it
+    fn run_on_caller(&self, job: Job) {
+        // Count the job as if it had been queued, so the counters stay consistent
+        self.pending.fetch_add(1, Ordering::SeqCst);
+        self.queued.fetch_add(1, Ordering::SeqCst);
+        self.accept_jobs(1);
+
+        self.run(job, None);
+    }
+
+    /// Pushes every job onto the queue, handing back the ones which weren't
+    /// pushed if the queue is closed
+    fn push_all(&self, priority: u8, jobs: Vec<Job>) -> Result<(), Vec<Job>> {
+        let count = jobs.len();
+        self.add_jobs(count);
+
+        let result = self.queue.push_all(self.target(priority), jobs);
+        self.settle_jobs(count, result)
+    }
+
+    /// Pushes one of the jobs created from the number of workers for every
+    /// worker, see [`Queue::push_broadcast`]
+    fn push_broadcast<F>(&self, make: F) -> Result<(), Vec<Job>>
+    where
+        F: FnOnce(usize) -> Vec<Job>,
+    {
+        let mut count = 0;
+
+        let result = self.queue.push_broadcast(|workers| {
+            let jobs = make(workers);
+            count = jobs.len();
+            self.add_jobs(count);
+            jobs
+        });
+
+        self.settle_jobs(count, result)
+    }
+
+    /// Updates the counters for `count` jobs which were pushed at once,
+    /// handing back the ones which weren't
+    fn settle_jobs(&self, count: usize, result: Result<(), Vec<Job>>) -> Result<(), Vec<Job>> {
+        let rejected = result.as_ref().map_or_else(Vec::len, |()| 0);
+
+        self.accept_jobs(count - rejected);
+        self.reject_jobs(rejected);
+
+        result
+    }
+
+    /// Counts jobs which have been queued as submitted
+    fn accept_jobs(&self, count: usize) {
+        self.submitted.fetch_add(count as u64, Ordering::SeqCst);
+    }
+
+    /// Counts the given number of jobs as queued, right before they are pushed
+    fn add_jobs(&self, count: usize) {
+        self.pending.fetch_add(count, Ordering::SeqCst);
+        self.queued.fetch_add(count, Ordering::SeqCst);
+
+        for _ in 0..count {
+            self.emit(|| PoolEvent::JobSubmitted);
+        }
+    }
+
+    /// Sends an event to the event sink, if there is one
+    ///
+    /// The event is only created if it is sent. Sending never blocks, and a
+    /// disconnected sink is ignored.
+    fn emit(&self, event: impl FnOnce() -> PoolEvent) {
+        if let Some(sink) = &self.event_sink {
+            let _ = sink.send(event());
+        }
+    }
+
+    /// Returns where a job with the given priority should be pushed to
+    fn target(&self, priority: u8) -> Target {
+        if self.scheduler == Scheduler::WorkStealing && priority == 0 {
+            if let Some(index) = self.current_worker() {
+                return Target::Local(index);
+            }
+        }
+
+        Target::Global(priority)
+    }
+
+    /// Returns the index of the worker running on the current thread if it
+    /// belongs to this pool
+    fn current_worker(&self) -> Option<usize> {
+        match CURRENT_WORKER.with(Cell::get) {
+            Some((id, index)) if id == self.id => Some(index),
+            _ => None,
+        }
+    }
+
+    /// Panics if called from a worker of this pool, where waiting for the
+    /// pool to be idle would wait for the calling job itself
+    fn assert_not_worker(&self, method: &str) {
+        assert!(
+            self.current_worker().is_none(),
+            "ThreadPool::{method} was called from within a job of the same pool, which would deadlock"
+        );
+    }
+
+    /// Moves from the state `from` to `to`, unless the pool is in another state
+    fn transition(&self, from: PoolState, to: PoolState) {
+        let (from, to) = (from as u8, to as u8);
+        let _ = self.state.compare_exchange(from, to, Ordering::SeqCst, Ordering::SeqCst);
+    }
+
+    /// Moves forward to `state`, unless the pool is past it already
+    fn advance(&self, state: PoolState) {
+        self.state.fetch_max(state as u8, Ordering::SeqCst);
+    }
+
+    /// Returns why the queue rejected a job
+    fn rejection(&self) -> ThreadPoolError {
+        if self.queue.is_closed() {
+            ThreadPoolError::PoolShutdown
+        } else {
+            ThreadPoolError::Draining
+        }
+    }
+
+    /// Reverts the counters for jobs which were counted but couldn't be queued
+    fn reject_jobs(&self, count: usize) {
+        self.queued.fetch_sub(count, Ordering::SeqCst);
+
+        for _ in 0..count {
+            self.release_pending();
+        }
+    }
+
+    /// Stops counting the size of a job which left the queue, see [`ThreadPool::execute_sized`]
+    fn unqueue_bytes(&self, job: &Job) {
+        if job.size > 0 {
+            self.queued_bytes.fetch_sub(job.size, Ordering::SeqCst);
+        }
+    }
+
+    /// Runs a popped job on the current thread, recording it in the worker's slot
+    fn run(&self, job: Job, slot: Option<&WorkerSlot>) {
+        self.unqueue_bytes(&job);
+        // A stale job is dropped without running it
+        if job.deadline.is_some_and(|deadline| Instant::now() > deadline) {
+            self.queued.fetch_sub(1, Ordering::SeqCst);
+            self.release_pending();
+            return;
+        }
+
+        if let Some(rate_limiter) = &self.rate_limiter {
+            rate_limiter.acquire();
+        }
+
+        if let Some(slot) = slot {
+            slot.start(job.name.clone());
+        }
+
+        self.start_job();
+
+        // Only clone the name if there is anyone to send it to
+        let event_name = self.event_sink.as_ref().and(job.name.clone());
+        self.emit(|| PoolEvent::JobStarted { name: event_name.clone() });
+
+        let named = job.name.is_some();
+
+        if named {
+            CURRENT_JOB_NAME.with(|current| *current.borrow_mut() = job.name);
+        }
+
+        // Only measure the time if anyone is interested in it
+        let timed = self.event_sink.is_some() || self.timing_callback.is_some();
+        let start = timed.then(Instant::now);
+
+        // A panicking job must not take the worker down with it,
+        // the panic message has already been printed by the panic hook
+        let result = panic::catch_unwind(AssertUnwindSafe(job.f));
+        let duration = start.map_or(Duration::ZERO, |start| start.elapsed());
+
+        if let Err(payload) = result {
+            self.failed.fetch_add(1, Ordering::SeqCst);
+            self.emit(|| PoolEvent::JobPanicked { name: event_name.clone() });
+
+            if let Some(handler) = &self.panic_handler {
+                // Neither must a panicking handler
+                let _ = panic::catch_unwind(AssertUnwindSafe(|| handler(payload)));
+            }
+        }
+
+        if let Some(on_complete) = &self.on_complete {
+            let _ = panic::catch_unwind(AssertUnwindSafe(|| on_complete()));
+        }
+
+        if let Some(timing_callback) = &self.timing_callback {
+            let _ = panic::catch_unwind(AssertUnwindSafe(|| timing_callback(duration)));
+        }
+
+        // The name is cleared only now, so the callbacks can still see it
+        if named {
+            CURRENT_JOB_NAME.with(|current| current.borrow_mut().take());
+        }
+
+        if let Some(slot) = slot {
+            slot.finish();
+        }
+
+        self.emit(|| PoolEvent::JobFinished { name: event_name, duration });
+        self.finish_job();
+    }
+
+    /// Marks a popped job as executing
+    fn start_job(&self) {
+        // Incrementing first keeps the job visible in at least one counter
+        self.active.fetch_add(1, Ordering::SeqCst);
+        self.queued.fetch_sub(1, Ordering::SeqCst);
+    }
+
+    /// Marks an executed job as finished
+    fn finish_job(&self) {
+        self.completed.fetch_add(1, Ordering::SeqCst);
+        self.active.fetch_sub(1, Ordering::SeqCst);
+        self.release_pending();
+    }
 
-    sender: mpsc::Sender<Job>,
+    /// Decrements `pending` and wakes up everyone waiting for the pool to be idle
+    fn release_pending(&self) {
+        if self.pending.fetch_sub(1, Ordering::SeqCst) == 1 {
+            let _guard = self.lock.lock().unwrap_or_else(PoisonError::into_inner);
+            self.idle.notify_all();
+        }
+    }
 }
 
 /// The actual `Job` executed by a `Worker`
-type Job = Option<Box<(dyn FnOnce() + Send + 'static)>>;
+struct Job {
+    f: Box<dyn FnOnce() + Send + 'static>,
+
+    /// The name given to [`ThreadPool::execute_named`]
+    name: Option<String>,
+
+    /// The job is skipped if it is popped after this
+    deadline: Option<Instant>,
+
+    /// The size of the closure given to [`ThreadPool::execute_sized`], 0 otherwise
+    size: usize,
+}
+
+impl Job {
+    fn new<F>(f: F) -> Self
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        Self::from_box(Box::new(f))
+    }
+
+    /// Creates a job from a closure which is already boxed, without boxing it again
+    fn from_box(f: Box<dyn FnOnce() + Send + 'static>) -> Self {
+        Self {
+            f,
+            name: None,
+            deadline: None,
+            size: 0,
+        }
+    }
+}
+
+/// Runs a job whose result goes to a `JobHandle`, catching its panic for the handle
+fn catch_result<T>(f: impl FnOnce() -> T) -> Result<T, JobError> {
+    panic::catch_unwind(AssertUnwindSafe(f)).map_err(JobError::Panic)
+}
+
+/// Runs a job like `catch_result`, recording when it started and finished
+fn catch_timed<T>(submitted: Instant, f: impl FnOnce() -> T) -> Outcome<T> {
+    let started = Instant::now();
+    let result = catch_result(f);
+
+    (result, Some(JobTiming { submitted, started, finished: Instant::now() }))
+}
+
+/// Creates the given attempt of a job submitted with [`ThreadPool::execute_with_retry`]
+///
+/// Holds the pool weakly, so queued retries don't keep it alive.
+fn retry_job<F>(shared: Weak<Shared>, f: Arc<F>, attempt: u32, max_attempts: u32) -> Job
+where
+    F: Fn() + Send + Sync + 'static,
+{
+    Job::new(move || {
+        let Err(payload) = panic::catch_unwind(AssertUnwindSafe(|| f())) else {
+            return;
+        };
+
+        if attempt < max_attempts {
+            if let Some(shared) = shared.upgrade() {
+                let retry = retry_job(Arc::downgrade(&shared), f, attempt + 1, max_attempts);
+
+                // Waiting for room could deadlock, since this worker may be the one to make it
+                if shared.push_unbounded(0, retry).is_ok() {
+                    return;
+                }
+            }
+        }
+
+        // Let the worker pass the last panic to the panic handler
+        panic::resume_unwind(payload);
+    })
+}
+
+/// Turns a job which was handed back by the queue into the closure it was created from
+///
+/// # Safety
+///
+/// The job must have been created from an `F`.
+unsafe fn into_closure<F>(job: Job) -> F {
+    // The data pointer of the trait object points to an `F`
+    *Box::from_raw(Box::into_raw(job.f) as *mut F)
+}
 
 impl ThreadPool {
+    /// Creates a pool with `size` workers
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is zero, see [`ThreadPool::try_new`].
     pub fn new(size: usize) -> Self {
-        assert!(size > 0);
+        Self::try_new(size).unwrap_or_else(|err| panic!("{err}"))
+    }
 
-        let (sender, receiver) = mpsc::channel();
-        let receiver = Arc::new(Mutex::new(receiver));
+    /// Creates a pool with `size` workers, returning an error if `size` is zero
+    /// or if a worker thread can't be spawned
+    ///
+    /// The workers which were already spawned are shut down and joined before
+    /// [`ThreadPoolError::SpawnFailed`] is returned, see [`ThreadPoolBuilder::try_build`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use threatpool::{ThreadPool, ThreadPoolError};
+    ///
+    /// assert_eq!(ThreadPool::try_new(0).err(), Some(ThreadPoolError::InvalidSize));
+    /// assert!(ThreadPool::try_new(4).is_ok());
+    /// ```
+    pub fn try_new(size: usize) -> Result<Self, ThreadPoolError> {
+        ThreadPoolBuilder::new().num_threads(size).try_build()
+    }
+
+    /// Returns a builder to configure a new pool, see [`ThreadPoolBuilder`]
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use threatpool::ThreadPool;
+    ///
+    /// let pool = ThreadPool::builder()
+    ///     .num_threads(4)
+    ///     .thread_name_prefix("my-pool")
+    ///     .build();
+    /// ```
+    pub fn builder() -> ThreadPoolBuilder {
+        ThreadPoolBuilder::new()
+    }
+
+    pub(crate) fn from_builder(builder: ThreadPoolBuilder) -> Result<Self, ThreadPoolError> {
+        let size = builder.num_threads.unwrap_or_else(default_size);
+
+        if size == 0 {
+            return Err(ThreadPoolError::InvalidSize);
+        }
+
+        assert!(builder.queue_capacity != Some(0));
+        assert!(builder.min_threads != Some(0));
+        assert!(builder.rate_limit != Some(0));
+        assert!(builder.slow_job_threshold != Some(Duration::ZERO));
+
+        let shared = Arc::new(Shared {
+            id: NEXT_POOL_ID.fetch_add(1, Ordering::SeqCst),
+            queue: Queue::new(
+                builder.job_queue.as_ref().map(|make_queue| make_queue()),
+                builder.queue_capacity,
+                builder.scheduler,
+                builder.order,
+                builder.dispatch,
+                builder.keep_alive,
+                builder.min_threads.unwrap_or(1),
+            ),
+            scheduler: builder.scheduler,
+            state: AtomicU8::new(PoolState::Running as u8),
+            pending: AtomicUsize::new(0),
+            queued: AtomicUsize::new(0),
+            active: AtomicUsize::new(0),
+            completed: AtomicU64::new(0),
+            submitted: AtomicU64::new(0),
+            failed: AtomicU64::new(0),
+            queued_bytes: AtomicUsize::new(0),
+            idle_workers: AtomicUsize::new(0),
+            lock: Mutex::new(()),
+            idle: Condvar::new(),
+            panic_handler: builder.panic_handler.clone(),
+            on_complete: builder.on_complete.clone(),
+            timing_callback: builder.timing_callback.clone(),
+            rate_limiter: builder.rate_limit.map(RateLimiter::new),
+            event_sink: builder.event_sink.clone(),
+            exited: Mutex::new(Vec::new()),
+            worker_exited: Condvar::new(),
+            expired: Mutex::new(Vec::new()),
+        });
 
         let mut workers = Vec::with_capacity(size);
 
-        for _ in 0..size {
-            workers.push(Worker::new(Arc::clone(&receiver)));
+        for index in 0..size {
+            match Worker::new(index, Arc::clone(&shared), &builder) {
+                Ok(worker) => workers.push(worker),
+
+                Err(_) => {
+                    shut_down_spawned(&shared, workers);
+                    return Err(ThreadPoolError::SpawnFailed);
+                }
+            }
         }
 
-        Self {
-            workers,
-            sender,
+        let inner = Arc::new(Inner {
+            workers: Mutex::new(workers),
+            shared,
+            timer: Mutex::new(None),
+            blocking: Arc::new(BlockingLane::new(&builder)),
+            builder,
+            next_index: AtomicUsize::new(size),
+            size: AtomicUsize::new(size),
+        });
+
+        if let Some(threshold) = inner.builder.slow_job_threshold {
+            if watchdog::spawn(Arc::downgrade(&inner), threshold).is_err() {
+                let workers = mem::take(&mut *inner.lock_workers());
+                shut_down_spawned(&inner.shared, workers);
+                return Err(ThreadPoolError::SpawnFailed);
+            }
         }
+
+        Ok(Self { inner })
     }
 
     /// Executes a given job
     ///
+    /// # Panics
+    ///
+    /// Panics if the pool is no longer accepting jobs, see [`ThreadPool::try_execute`].
+    ///
     /// # Example
     ///
     /// ```
@@ -63,58 +731,4031 @@ impl ThreadPool {
     where
         F: FnOnce() + Send + 'static,
     {
-        let job: Job = Some(Box::new(f));
-        self.sender.send(job).unwrap();
+        self.try_execute(f).unwrap();
     }
-}
-
-struct Worker {
-    thread: Option<thread::JoinHandle<()>>,
-}
 
-impl Worker {
-    fn new(receiver: Arc<Mutex<mpsc::Receiver<Job>>>) -> Worker {
-        let thread = thread::spawn(move || loop {
-            let job = receiver.lock().unwrap().recv().unwrap();
+    /// Executes a job which is already boxed, without boxing it again
+    ///
+    /// # Panics
+    ///
+    /// Panics if the pool is no longer accepting jobs.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use threatpool::ThreadPool;
+    ///
+    /// let pool = ThreadPool::new(4);
+    ///
+    /// let jobs: Vec<Box<dyn FnOnce() + Send>> = vec![
+    ///     Box::new(|| println!("Hello")),
+    ///     Box::new(|| println!("World")),
+    /// ];
+    ///
+    /// for job in jobs {
+    ///     pool.execute_boxed(job);
+    /// }
+    /// ```
+    pub fn execute_boxed(&self, job: Box<dyn FnOnce() + Send + 'static>) {
+        if self.inner.shared.push(0, Job::from_box(job)).is_err() {
+            panic!("the thread pool is no longer accepting jobs");
+        }
 
-            match job {
-                Some(job) => {
-                    job();
-                }
+        self.inner.grow();
+    }
 
-                None => {
-                    break; // Breaks if the given job is `None`
-                }
-            }
-        });
+    /// Executes every given job, which is equivalent to calling
+    /// [`ThreadPool::execute`] for each of them
+    ///
+    /// The whole batch is queued while locking the queue only once.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the pool is no longer accepting jobs.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use threatpool::ThreadPool;
+    ///
+    /// let pool = ThreadPool::new(4);
+    ///
+    /// pool.execute_all((0..4).map(|i| move || println!("Job {i}")));
+    /// ```
+    pub fn execute_all<I, F>(&self, jobs: I)
+    where
+        I: IntoIterator<Item = F>,
+        F: FnOnce() + Send + 'static,
+    {
+        let jobs = jobs.into_iter().map(Job::new).collect();
 
-        Self {
-            thread: Some(thread),
+        if self.inner.shared.push_all(0, jobs).is_err() {
+            panic!("the thread pool is no longer accepting jobs");
         }
+
+        self.inner.grow();
     }
-}
 
-impl Drop for ThreadPool {
-    fn drop(&mut self) {
-        for _ in &self.workers {
-            self.sender.send(None).unwrap();
+    /// Executes a given job with a priority, jobs with a higher priority are
+    /// executed first
+    ///
+    /// Jobs with the same priority are guaranteed to be started in the order
+    /// they were submitted, so a pool with a single worker also finishes them
+    /// in that order. Plain [`ThreadPool::execute`] uses priority 0. With
+    /// [`Scheduler::WorkStealing`], jobs a worker submits to its own local
+    /// queue don't take part in this order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the pool is no longer accepting jobs.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use threatpool::ThreadPool;
+    ///
+    /// let pool = ThreadPool::new(4);
+    ///
+    /// pool.execute_with_priority(10, || {
+    ///     println!("Important");
+    /// });
+    /// ```
+    pub fn execute_with_priority<F>(&self, priority: u8, f: F)
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        self.submit(priority, None, f).unwrap();
+    }
+
+    /// Executes a given job, handing it back if the pool is no longer accepting jobs
+    ///
+    /// This happens once the pool has been shut down through one of its clones.
+    /// Blocks while a bounded queue is full.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use threatpool::ThreadPool;
+    ///
+    /// let pool = ThreadPool::new(4);
+    ///
+    /// if let Err(err) = pool.try_execute(|| println!("Hello, World")) {
+    ///     // Run the rejected job on the current thread instead
+    ///     (err.into_inner())();
+    /// }
+    /// ```
+    pub fn try_execute<F>(&self, f: F) -> Result<(), ExecuteError<F>>
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        self.submit(0, None, f)
+    }
+
+    /// Executes a given job with a name, which makes it easier to tell which
+    /// job panicked or hangs
+    ///
+    /// While the job runs, its name is returned by [`current_job_name`],
+    /// including in the panic handler if the job panics.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the pool is no longer accepting jobs.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use threatpool::ThreadPoolBuilder;
+    ///
+    /// let pool = ThreadPoolBuilder::new()
+    ///     .panic_handler(|_| {
+    ///         eprintln!("{:?} panicked", threatpool::current_job_name());
+    ///     })
+    ///     .build();
+    ///
+    /// pool.execute_named("resize-images", || {
+    ///     println!("Resizing");
+    /// });
+    /// ```
+    pub fn execute_named<F>(&self, name: impl Into<String>, f: F)
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        self.submit(0, Some(name.into()), f).unwrap();
+    }
+
+    /// Executes a given job unless a worker only picks it up after `deadline`
+    ///
+    /// A job which missed its deadline is dropped without running, it doesn't
+    /// count as completed. A job which has started is never interrupted.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the pool is no longer accepting jobs.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::time::{Duration, Instant};
+    /// use threatpool::ThreadPool;
+    ///
+    /// let pool = ThreadPool::new(4);
+    ///
+    /// // The answer is useless after 100ms
+    /// pool.execute_with_deadline(Instant::now() + Duration::from_millis(100), || {
+    ///     println!("Still in time");
+    /// });
+    /// ```
+    pub fn execute_with_deadline<F>(&self, deadline: Instant, f: F)
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        let job = Job {
+            deadline: Some(deadline),
+            ..Job::new(f)
+        };
+
+        if self.inner.shared.push(0, job).is_err() {
+            panic!("the thread pool is no longer accepting jobs");
+        }
+
+        self.inner.grow();
+    }
+
+    /// Executes a given job like [`ThreadPool::execute`], counting the size of
+    /// the closure towards [`ThreadPool::queued_bytes`] while it is queued
+    ///
+    /// The size is the one of `F` itself, i.e. the data it captures by value,
+    /// not any heap memory the captured data owns.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the pool is no longer accepting jobs.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use threatpool::ThreadPool;
+    ///
+    /// let pool = ThreadPool::new(4);
+    /// let data = [0u8; 1024];
+    ///
+    /// pool.execute_sized(move || println!("{}", data.len()));
+    /// println!("{} bytes queued", pool.queued_bytes());
+    /// ```
+    pub fn execute_sized<F>(&self, f: F)
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        let shared = &self.inner.shared;
+        let size = mem::size_of::<F>();
+
+        // Counting before pushing keeps a worker from subtracting the size first
+        shared.queued_bytes.fetch_add(size, Ordering::SeqCst);
+
+        if shared.push(0, Job { size, ..Job::new(f) }).is_err() {
+            shared.queued_bytes.fetch_sub(size, Ordering::SeqCst);
+            panic!("the thread pool is no longer accepting jobs");
+        }
+
+        self.inner.grow();
+    }
+
+    /// Executes a given job on the worker with the given index
+    ///
+    /// This requires [`Dispatch::RoundRobin`], where every worker owns a queue
+    /// which no other worker pops from. The index is the one of
+    /// [`WorkerHealth::index`] and the worker's thread name. If the worker is
+    /// removed before it picks up the job, the job is handed to another one.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the pool doesn't use [`Dispatch::RoundRobin`], if there is no
+    /// worker with the given index or if the pool is no longer accepting jobs.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use threatpool::{Dispatch, ThreadPoolBuilder};
+    ///
+    /// let pool = ThreadPoolBuilder::new()
+    ///     .num_threads(2)
+    ///     .dispatch(Dispatch::RoundRobin)
+    ///     .build();
+    ///
+    /// pool.execute_on(1, || {
+    ///     assert_eq!(std::thread::current().name(), Some("threatpool-worker-1"));
+    /// });
+    /// ```
+    pub fn execute_on<F>(&self, worker_index: usize, f: F)
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        let shared = &self.inner.shared;
+
+        assert!(
+            self.inner.builder.dispatch == Dispatch::RoundRobin,
+            "ThreadPool::execute_on requires Dispatch::RoundRobin"
+        );
+
+        assert!(
+            shared.queue.has_own_queue(worker_index),
+            "there is no worker with index {worker_index}"
+        );
+
+        if shared.push_to(Target::Pinned(worker_index), Job::new(f)).is_err() {
+            panic!("the thread pool is no longer accepting jobs");
+        }
+    }
+
+    /// Executes a given job and queues it again whenever it panics, up to
+    /// `max_attempts` times in total
+    ///
+    /// Only the panic of the last attempt is passed to the panic handler.
+    /// Retries are queued behind the jobs which are already waiting, even if
+    /// the queue is full, and every attempt counts as a completed job. If the pool stops accepting
+    /// jobs in between, the job isn't retried any further.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_attempts` is zero or the pool is no longer accepting jobs.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use threatpool::ThreadPool;
+    ///
+    /// let pool = ThreadPool::new(4);
+    ///
+    /// pool.execute_with_retry(3, || {
+    ///     println!("Fetching a flaky resource");
+    /// });
+    /// ```
+    pub fn execute_with_retry<F>(&self, max_attempts: u32, f: F)
+    where
+        F: Fn() + Send + Sync + 'static,
+    {
+        assert!(max_attempts > 0);
+
+        let job = retry_job(Arc::downgrade(&self.inner.shared), Arc::new(f), 1, max_attempts);
+
+        if self.inner.shared.push(0, job).is_err() {
+            panic!("the thread pool is no longer accepting jobs");
+        }
+
+        self.inner.grow();
+    }
+
+    fn submit<F>(&self, priority: u8, name: Option<String>, f: F) -> Result<(), ExecuteError<F>>
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        let shared = &self.inner.shared;
+        let job = Job { name, ..Job::new(f) };
+
+        // SAFETY: The job was created from an `F` above
+        let reject = |job, error| ExecuteError::new(unsafe { into_closure(job) }, error);
+
+        match self.inner.builder.rejection_policy {
+            RejectionPolicy::Block => {
+                shared.push(priority, job).map_err(|job| reject(job, shared.rejection()))?;
+            }
+
+            RejectionPolicy::DiscardOldest => {
+                shared.push_displacing(priority, job).map_err(|job| reject(job, shared.rejection()))?;
+            }
+
+            policy => match shared.try_push(priority, job) {
+                Ok(()) => {}
+
+                Err(TryPushError::Full(job)) if policy == RejectionPolicy::CallerRuns => {
+                    shared.run_on_caller(job);
+                }
+
+                Err(TryPushError::Full(job)) => return Err(reject(job, ThreadPoolError::QueueFull)),
+                Err(TryPushError::Closed(job)) => return Err(reject(job, shared.rejection())),
+            },
+        }
+
+        self.inner.grow();
+
+        Ok(())
+    }
+
+    /// Executes a given job without blocking, handing it back if the bounded
+    /// queue is full or the pool is no longer accepting jobs
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use threatpool::{ThreadPoolBuilder, TryExecuteError};
+    ///
+    /// let pool = ThreadPoolBuilder::new()
+    ///     .num_threads(4)
+    ///     .queue_capacity(16)
+    ///     .build();
+    ///
+    /// match pool.try_execute_nonblocking(|| println!("Hello, World")) {
+    ///     Ok(()) => {}
+    ///     Err(TryExecuteError::Full(job)) => job(),
+    ///     Err(TryExecuteError::Draining(_)) => panic!("the pool is draining"),
+    ///     Err(TryExecuteError::Disconnected(_)) => panic!("the pool is gone"),
+    /// }
+    /// ```
+    pub fn try_execute_nonblocking<F>(&self, f: F) -> Result<(), TryExecuteError<F>>
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        let job = Job::new(f);
+
+        self.inner.shared.try_push(0, job).map_err(|err| {
+            // SAFETY: The job was created from an `F` above
+            match err {
+                TryPushError::Full(job) => TryExecuteError::Full(unsafe { into_closure(job) }),
+
+                TryPushError::Closed(job) => {
+                    let job = unsafe { into_closure(job) };
+
+                    match self.inner.shared.rejection() {
+                        ThreadPoolError::Draining => TryExecuteError::Draining(job),
+                        _ => TryExecuteError::Disconnected(job),
+                    }
+                }
+            }
+        })?;
+
+        self.inner.grow();
+
+        Ok(())
+    }
+
+    /// Executes a given job if the bounded queue has room for it, handing it
+    /// back otherwise
+    ///
+    /// The room is checked and the job queued under the same lock, so unlike
+    /// checking [`ThreadPool::remaining_capacity`] first, this never queues
+    /// more jobs than there is room for when several threads submit at once.
+    /// Never blocks. The job is also handed back if the pool is no longer
+    /// accepting jobs, use [`ThreadPool::try_execute_nonblocking`] to tell
+    /// these cases apart.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use threatpool::ThreadPoolBuilder;
+    ///
+    /// let pool = ThreadPoolBuilder::new().queue_capacity(16).build();
+    ///
+    /// if let Err(job) = pool.execute_if_capacity(|| println!("Hello, World")) {
+    ///     // Shed the load by running the job somewhere else
+    ///     job();
+    /// }
+    /// ```
+    pub fn execute_if_capacity<F>(&self, f: F) -> Result<(), F>
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        self.try_execute_nonblocking(f).map_err(TryExecuteError::into_inner)
+    }
+
+    /// Executes a given job which blocks for a long time, e.g. on IO
+    ///
+    /// Blocking jobs don't run on the workers but on a separate set of
+    /// threads, which are spawned whenever all of them are busy and exit
+    /// after being idle for a while. This keeps the workers free for short
+    /// jobs. Blocking jobs are included in [`ThreadPool::join`] and the metrics.
+    ///
+    /// If a new thread can't be spawned, the job waits for one of the busy
+    /// threads instead.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the pool is no longer accepting jobs, or if there is no
+    /// thread to run the job and spawning one fails.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use threatpool::ThreadPool;
+    ///
+    /// let pool = ThreadPool::new(4);
+    ///
+    /// pool.execute_blocking(|| {
+    ///     // Simulate waiting for a slow disk
+    ///     std::thread::sleep(Duration::from_millis(10));
+    /// });
+    /// ```
+    pub fn execute_blocking<F>(&self, f: F)
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        let shared = &self.inner.shared;
+
+        if !shared.queue.is_accepting() {
+            panic!("the thread pool is no longer accepting jobs");
+        }
+
+        shared.add_jobs(1);
+
+        match self.inner.blocking.push(shared, Job::new(f)) {
+            Ok(()) => shared.accept_jobs(1),
+
+            Err(PushError::Closed) => {
+                shared.reject_jobs(1);
+                panic!("the thread pool is no longer accepting jobs");
+            }
+
+            Err(PushError::SpawnFailed(err)) => {
+                shared.reject_jobs(1);
+                panic!("failed to spawn a blocking thread: {err}");
+            }
+        }
+    }
+
+    /// Executes a given job once `delay` has passed
+    ///
+    /// The job is submitted by a timer thread which is spawned on first use.
+    /// A zero `delay` behaves like [`ThreadPool::execute`]. Delayed jobs are
+    /// only counted as submitted, e.g. by [`ThreadPool::join`], once they are
+    /// due, and jobs which aren't due yet are dropped when the pool shuts down.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the timer thread has to be spawned and spawning it fails.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use threatpool::ThreadPool;
+    ///
+    /// let pool = ThreadPool::new(4);
+    ///
+    /// pool.execute_after(Duration::from_millis(10), || {
+    ///     println!("Hello, World");
+    /// });
+    /// ```
+    pub fn execute_after<F>(&self, delay: Duration, f: F)
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        if delay.is_zero() {
+            return self.execute(f);
+        }
+
+        let deadline = Instant::now() + delay;
+
+        self.inner
+            .lock_timer()
+            .get_or_insert_with(|| Timer::new(Arc::clone(&self.inner.shared)))
+            .schedule(deadline, Job::new(f));
+    }
+
+    /// Executes a given job every `interval` until it is stopped through the
+    /// returned [`RecurringHandle`]
+    ///
+    /// The job is submitted by the timer thread, like the jobs of
+    /// [`ThreadPool::execute_after`], and first runs once `interval` has
+    /// passed. A run is skipped while the previous one is still running, and
+    /// runs which the timer missed aren't caught up on. A panicking run is
+    /// passed to the panic handler without stopping the later ones. The job
+    /// stops when the pool shuts down.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `interval` is zero, or if the timer thread has to be spawned
+    /// and spawning it fails.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use threatpool::ThreadPool;
+    ///
+    /// let pool = ThreadPool::new(4);
+    ///
+    /// let mut ticks = 0;
+    /// let handle = pool.schedule_recurring(Duration::from_millis(10), move || {
+    ///     ticks += 1;
+    ///     println!("Tick {ticks}");
+    /// });
+    ///
+    /// std::thread::sleep(Duration::from_millis(50));
+    /// handle.stop();
+    /// ```
+    pub fn schedule_recurring<F>(&self, interval: Duration, f: F) -> RecurringHandle
+    where
+        F: FnMut() + Send + 'static,
+    {
+        assert!(!interval.is_zero());
+
+        let task = Arc::new(Recurring::new(interval, f));
+
+        self.inner
+            .lock_timer()
+            .get_or_insert_with(|| Timer::new(Arc::clone(&self.inner.shared)))
+            .schedule_recurring(Arc::clone(&task));
+
+        RecurringHandle::new(task)
+    }
+
+    /// Blocks until every job submitted so far has finished
+    ///
+    /// Jobs submitted by other threads while waiting are waited for as well.
+    /// The pool stays usable afterwards.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use threatpool::ThreadPool;
+    ///
+    /// let pool = ThreadPool::new(4);
+    ///
+    /// pool.execute(|| {
+    ///     println!("Hello, World");
+    /// });
+    ///
+    /// pool.join();
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if called from within a job of the same pool, since the job
+    /// would wait for itself to finish.
+    pub fn join(&self) {
+        let shared = &self.inner.shared;
+        shared.assert_not_worker("join");
+
+        let mut guard = shared.lock.lock().unwrap_or_else(PoisonError::into_inner);
+
+        while shared.pending.load(Ordering::SeqCst) > 0 {
+            guard = shared.idle.wait(guard).unwrap_or_else(PoisonError::into_inner);
+        }
+    }
+
+    /// Blocks until every job submitted so far has finished, running queued
+    /// jobs on the calling thread in the meantime
+    ///
+    /// The calling thread pops jobs like an extra worker until the queue is
+    /// empty, then waits for the running ones like [`ThreadPool::join`]. This
+    /// speeds up submitting a batch and waiting for it. With
+    /// [`Dispatch::RoundRobin`] every job belongs to a worker, so this is the
+    /// same as `join`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called from within a job of the same pool, like [`ThreadPool::join`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use threatpool::ThreadPool;
+    ///
+    /// let pool = ThreadPool::new(4);
+    ///
+    /// for i in 0..16 {
+    ///     pool.execute(move || println!("job {i}"));
+    /// }
+    ///
+    /// pool.join_and_help();
+    /// assert!(pool.is_idle());
+    /// ```
+    pub fn join_and_help(&self) {
+        let shared = &self.inner.shared;
+        shared.assert_not_worker("join_and_help");
+
+        while let Some(job) = shared.queue.try_pop() {
+            shared.run(job, None);
+        }
+
+        self.join();
+    }
+
+    /// Blocks until every job submitted so far has finished or `timeout` has passed
+    ///
+    /// Returns `true` if the pool became idle and `false` if the timeout
+    /// passed first. Like [`ThreadPool::join`], but without the risk of
+    /// waiting forever on a job which never finishes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called from within a job of the same pool, like [`ThreadPool::join`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use threatpool::ThreadPool;
+    ///
+    /// let pool = ThreadPool::new(4);
+    ///
+    /// pool.execute(|| {
+    ///     println!("Hello, World");
+    /// });
+    ///
+    /// assert!(pool.wait_for_idle(Duration::from_secs(1)));
+    /// ```
+    pub fn wait_for_idle(&self, timeout: Duration) -> bool {
+        let shared = &self.inner.shared;
+        shared.assert_not_worker("wait_for_idle");
+
+        let deadline = Instant::now() + timeout;
+        let mut guard = shared.lock.lock().unwrap_or_else(PoisonError::into_inner);
+
+        while shared.pending.load(Ordering::SeqCst) > 0 {
+            let now = Instant::now();
+
+            if now >= deadline {
+                return false;
+            }
+
+            let result = shared.idle.wait_timeout(guard, deadline - now);
+            guard = result.unwrap_or_else(PoisonError::into_inner).0;
+        }
+
+        true
+    }
+
+    /// Returns whether every job submitted so far has finished, without blocking
+    ///
+    /// The non-blocking counterpart of [`ThreadPool::join`] for polling from an
+    /// event loop, which is the same as [`ThreadPool::is_idle`]. Unlike `join`,
+    /// this may be called from within a job, though it never returns `true` there.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use threatpool::ThreadPool;
+    ///
+    /// let pool = ThreadPool::new(4);
+    /// pool.execute(|| println!("Hello, World"));
+    ///
+    /// while !pool.try_join() {
+    ///     // Do other work in the meantime
+    ///     std::thread::yield_now();
+    /// }
+    /// ```
+    pub fn try_join(&self) -> bool {
+        self.is_idle()
+    }
+
+    /// Stops accepting new jobs and blocks until every queued and running job
+    /// has finished
+    ///
+    /// Unlike [`ThreadPool::shutdown`] the workers keep running, so the pool
+    /// can start accepting jobs again with [`ThreadPool::reopen`]. While
+    /// draining, [`ThreadPool::try_execute`] hands jobs back and
+    /// [`ThreadPool::execute`] panics.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called from within a job of the same pool, like [`ThreadPool::join`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use threatpool::ThreadPool;
+    ///
+    /// let pool = ThreadPool::new(4);
+    ///
+    /// pool.execute(|| {
+    ///     println!("Hello, World");
+    /// });
+    ///
+    /// pool.drain();
+    /// assert!(pool.try_execute(|| {}).is_err());
+    ///
+    /// pool.reopen();
+    /// assert!(pool.try_execute(|| {}).is_ok());
+    /// ```
+    pub fn drain(&self) {
+        let shared = &self.inner.shared;
+        shared.assert_not_worker("drain");
+
+        shared.queue.set_draining(true);
+        shared.transition(PoolState::Running, PoolState::Draining);
+
+        self.join();
+    }
+
+    /// Starts accepting new jobs again after [`ThreadPool::drain`]
+    pub fn reopen(&self) {
+        let shared = &self.inner.shared;
+
+        shared.queue.set_draining(false);
+        shared.transition(PoolState::Draining, PoolState::Running);
+    }
+
+    /// Stops the workers from starting queued jobs until [`ThreadPool::resume`]
+    ///
+    /// Running jobs finish normally and new jobs are still accepted, they wait
+    /// in the queue along with the queued ones. Unlike [`ThreadPool::drain`],
+    /// no jobs are rejected. Waiting for the pool with [`ThreadPool::join`]
+    /// blocks until it is resumed. Shutting the pool down resumes it, so the
+    /// queued jobs still run before the workers exit.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use threatpool::ThreadPool;
+    ///
+    /// let pool = ThreadPool::new(4);
+    ///
+    /// pool.pause();
+    /// pool.execute(|| println!("Hello, World"));
+    /// assert_eq!(pool.queued_count(), 1);
+    ///
+    /// pool.resume();
+    /// pool.join();
+    /// ```
+    pub fn pause(&self) {
+        self.inner.shared.queue.set_paused(true);
+    }
+
+    /// Lets the workers start queued jobs again after [`ThreadPool::pause`]
+    pub fn resume(&self) {
+        self.inner.shared.queue.set_paused(false);
+    }
+
+    /// Returns whether the pool has been paused with [`ThreadPool::pause`]
+    pub fn is_paused(&self) -> bool {
+        self.inner.shared.queue.is_paused()
+    }
+
+    /// Returns the lifecycle state of the pool
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use threatpool::{PoolState, ThreadPool};
+    ///
+    /// let pool = ThreadPool::new(4);
+    /// assert_eq!(pool.state(), PoolState::Running);
+    ///
+    /// pool.drain();
+    /// assert_eq!(pool.state(), PoolState::Draining);
+    /// ```
+    pub fn state(&self) -> PoolState {
+        PoolState::from_u8(self.inner.shared.state.load(Ordering::SeqCst))
+    }
+
+    /// Executes a given job and returns a [`JobHandle`] to its result
+    ///
+    /// If the job panics, the panic is passed to the handle as
+    /// [`JobError::Panic`] instead of to the panic handler.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use threatpool::ThreadPool;
+    ///
+    /// let pool = ThreadPool::new(4);
+    ///
+    /// let handles: Vec<_> = (0..8)
+    ///     .map(|i| pool.execute_with_result(move || i * 2))
+    ///     .collect();
+    ///
+    /// for (i, handle) in handles.into_iter().enumerate() {
+    ///     assert_eq!(handle.join().unwrap(), i * 2);
+    /// }
+    /// ```
+    pub fn execute_with_result<F, T>(&self, f: F) -> JobHandle<T>
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        let (sender, receiver) = mpsc::channel();
+        let submitted = Instant::now();
+
+        self.execute(move || {
+            // The handle may have been dropped, in which case nobody wants the result
+            let _ = sender.send(catch_timed(submitted, f));
+        });
+
+        JobHandle::new(receiver)
+    }
+
+    /// Executes a given job like [`ThreadPool::execute_with_result`], which
+    /// can be cancelled through the returned [`CancelToken`] before a worker
+    /// picks it up
+    ///
+    /// Once the job is cancelled, the handle immediately resolves to
+    /// [`JobError::Cancelled`] instead of waiting for a worker to skip the job.
+    /// A job which has already started can't be cancelled.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the pool is no longer accepting jobs.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use threatpool::{JobError, ThreadPool};
+    ///
+    /// let pool = ThreadPool::new(1);
+    ///
+    /// pool.execute(|| std::thread::sleep(std::time::Duration::from_millis(100)));
+    /// let (handle, token) = pool.execute_tracked(|| 6 * 7);
+    ///
+    /// token.cancel();
+    /// assert!(matches!(handle.join(), Err(JobError::Cancelled)));
+    /// ```
+    pub fn execute_tracked<F, T>(&self, f: F) -> (JobHandle<T>, CancelToken)
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        let (sender, receiver) = mpsc::channel();
+        let submitted = Instant::now();
+
+        // Whoever takes the sender first decides how the handle resolves
+        let sender = Arc::new(Mutex::new(Some(sender)));
+        let cancel_sender = Arc::clone(&sender);
+
+        let token = CancelToken::new();
+        let job_token = token.clone();
+
+        token.on_cancel(move || {
+            let sender = cancel_sender.lock().unwrap_or_else(PoisonError::into_inner).take();
+
+            if let Some(sender) = sender {
+                let _ = sender.send((Err(JobError::Cancelled), None));
+            }
+        });
+
+        self.execute(move || {
+            let Some(sender) = sender.lock().unwrap_or_else(PoisonError::into_inner).take() else {
+                return;
+            };
+
+            if job_token.is_cancelled() {
+                let _ = sender.send((Err(JobError::Cancelled), None));
+            } else {
+                let _ = sender.send(catch_timed(submitted, f));
+            }
+        });
+
+        (JobHandle::new(receiver), token)
+    }
+
+    /// Executes a given job and returns an [`AckReceiver`] which is notified
+    /// once a worker starts running it
+    ///
+    /// # Panics
+    ///
+    /// Panics if the pool is no longer accepting jobs.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use threatpool::{AckReceiver, ThreadPoolBuilder};
+    ///
+    /// let pool = ThreadPoolBuilder::new()
+    ///     .num_threads(4)
+    ///     .queue_capacity(16)
+    ///     .build();
+    ///
+    /// let mut acks: Vec<AckReceiver> = Vec::new();
+    ///
+    /// for i in 0..100 {
+    ///     // Never get more than 8 jobs ahead of the workers
+    ///     if acks.len() == 8 {
+    ///         acks.remove(0).wait();
+    ///     }
+    ///
+    ///     acks.push(pool.execute_acked(move || println!("Job {i}")));
+    /// }
+    /// ```
+    pub fn execute_acked<F>(&self, f: F) -> AckReceiver
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        let (sender, receiver) = mpsc::channel();
+
+        self.execute(move || {
+            // The receiver may have been dropped, in which case nobody is waiting
+            let _ = sender.send(());
+            f();
+        });
+
+        AckReceiver::new(receiver)
+    }
+
+    /// Executes a given job unless it is cancelled through the returned
+    /// [`CancelToken`] before a worker picks it up
+    ///
+    /// A job which has already started can't be cancelled.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use threatpool::ThreadPool;
+    ///
+    /// let pool = ThreadPool::new(4);
+    ///
+    /// let token = pool.execute_cancellable(|| {
+    ///     println!("Hello, World");
+    /// });
+    ///
+    /// token.cancel();
+    /// ```
+    pub fn execute_cancellable<F>(&self, f: F) -> CancelToken
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        let token = CancelToken::new();
+        let job_token = token.clone();
+
+        self.execute(move || {
+            if !job_token.is_cancelled() {
+                f();
+            }
+        });
+
+        token
+    }
+
+    /// Returns the number of jobs currently executing
+    pub fn active_count(&self) -> usize {
+        self.inner.shared.active.load(Ordering::SeqCst)
+    }
+
+    /// Returns the number of jobs waiting in the queue
+    ///
+    /// Jobs delayed with [`ThreadPool::execute_after`] are only counted once they are due.
+    pub fn queued_count(&self) -> usize {
+        self.inner.shared.queued.load(Ordering::SeqCst)
+    }
+
+    /// Returns the summed size in bytes of the queued closures which were
+    /// submitted with [`ThreadPool::execute_sized`]
+    ///
+    /// This is a rough gauge of the memory held by the queue, jobs submitted
+    /// in any other way aren't counted. Use [`ThreadPool::queued_count`] for
+    /// the number of queued jobs.
+    pub fn queued_bytes(&self) -> usize {
+        self.inner.shared.queued_bytes.load(Ordering::SeqCst)
+    }
+
+    /// Returns how many more jobs fit into the bounded queue before
+    /// submitting blocks, or `None` if the queue is unbounded
+    ///
+    /// The value is a snapshot which other threads may change right away.
+    /// With [`Dispatch::Synchronous`], this is whether a worker is waiting to
+    /// be handed a job, so either 0 or 1, since idle workers wait for a job
+    /// one at a time.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use threatpool::{ThreadPool, ThreadPoolBuilder};
+    ///
+    /// let pool = ThreadPoolBuilder::new().queue_capacity(16).build();
+    /// assert_eq!(pool.remaining_capacity(), Some(16));
+    ///
+    /// assert_eq!(ThreadPool::new(4).remaining_capacity(), None);
+    /// ```
+    pub fn remaining_capacity(&self) -> Option<usize> {
+        self.inner.shared.queue.remaining_capacity()
+    }
+
+    /// Returns the number of jobs which have finished, including the ones which panicked
+    pub fn completed_count(&self) -> u64 {
+        self.inner.shared.completed.load(Ordering::SeqCst)
+    }
+
+    /// Returns the number of jobs which panicked, which are also counted by
+    /// [`ThreadPool::completed_count`]
+    ///
+    /// This works with or without a panic handler, so a rising count is a
+    /// cheap health signal. Jobs whose panic is passed to a [`JobHandle`],
+    /// e.g. with [`ThreadPool::execute_with_result`], aren't counted.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use threatpool::ThreadPool;
+    ///
+    /// let pool = ThreadPool::new(4);
+    ///
+    /// pool.execute(|| panic!("boom"));
+    /// pool.join();
+    ///
+    /// assert_eq!(pool.failed_count(), 1);
+    /// ```
+    pub fn failed_count(&self) -> u64 {
+        self.inner.shared.failed.load(Ordering::SeqCst)
+    }
+
+    /// Returns the number of jobs which have been accepted, i.e. queued
+    ///
+    /// Rejected jobs aren't counted, and delayed jobs only once they are due.
+    /// Together with [`ThreadPool::completed_count`] this tracks the progress
+    /// of a batch, though jobs which missed their
+    /// [deadline](ThreadPool::execute_with_deadline) never complete.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use threatpool::ThreadPool;
+    ///
+    /// let pool = ThreadPool::new(4);
+    ///
+    /// for _ in 0..8 {
+    ///     pool.execute(|| println!("Working"));
+    /// }
+    ///
+    /// println!("{} / {} done", pool.completed_count(), pool.submitted_count());
+    /// ```
+    pub fn submitted_count(&self) -> u64 {
+        self.inner.shared.submitted.load(Ordering::SeqCst)
+    }
+
+    /// Returns the number of jobs which are queued or running
+    ///
+    /// Unlike adding up [`ThreadPool::queued_count`] and
+    /// [`ThreadPool::active_count`], this reads a single counter, so a job
+    /// moving from the queue to a worker is never missed.
+    pub fn pending(&self) -> usize {
+        self.inner.shared.pending.load(Ordering::SeqCst)
+    }
+
+    /// Returns whether no jobs are queued or running
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use threatpool::ThreadPool;
+    ///
+    /// let pool = ThreadPool::new(4);
+    ///
+    /// pool.execute(|| println!("Hello, World"));
+    ///
+    /// while !pool.is_idle() {
+    ///     std::thread::yield_now();
+    /// }
+    /// ```
+    pub fn is_idle(&self) -> bool {
+        self.pending() == 0
+    }
+
+    /// Returns the number of workers waiting for a job
+    ///
+    /// A worker counts as idle from the moment it asks the queue for a job
+    /// until it gets one or exits, so briefly also while jobs are queued.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use threatpool::ThreadPool;
+    ///
+    /// let pool = ThreadPool::new(4);
+    ///
+    /// assert!(pool.idle_count() <= 4);
+    /// ```
+    pub fn idle_count(&self) -> usize {
+        self.inner.shared.idle_workers.load(Ordering::SeqCst)
+    }
+
+    /// Returns a snapshot of all metrics of the pool
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use threatpool::ThreadPool;
+    ///
+    /// let pool = ThreadPool::new(4);
+    ///
+    /// pool.execute(|| {
+    ///     println!("Hello, World");
+    /// });
+    /// pool.join();
+    ///
+    /// assert_eq!(pool.stats().completed, 1);
+    /// ```
+    pub fn stats(&self) -> PoolStats {
+        PoolStats {
+            active: self.active_count(),
+            queued: self.queued_count(),
+            completed: self.completed_count(),
+            submitted: self.submitted_count(),
+            failed: self.failed_count(),
+            idle: self.idle_count(),
+        }
+    }
+
+    /// Returns the number of live workers
+    ///
+    /// This follows [`ThreadPool::add_workers`], [`ThreadPool::remove_workers`]
+    /// and workers expiring after the [keep-alive](ThreadPoolBuilder::keep_alive).
+    /// A worker which has been asked to exit no longer counts, even if it is
+    /// still finishing its current job.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use threatpool::ThreadPool;
+    ///
+    /// let pool = ThreadPool::new(4);
+    /// assert_eq!(pool.size(), 4);
+    ///
+    /// pool.add_workers(2).unwrap();
+    /// assert_eq!(pool.size(), 6);
+    /// ```
+    pub fn size(&self) -> usize {
+        self.inner.shared.queue.workers()
+    }
+
+    /// Returns what every worker is doing, sorted by the worker index
+    ///
+    /// A supervisor can use this to detect workers which are stuck on a job.
+    /// Only the workers are reported, not the threads of
+    /// [`ThreadPool::execute_blocking`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use threatpool::ThreadPool;
+    ///
+    /// let pool = ThreadPool::new(4);
+    ///
+    /// for worker in pool.health() {
+    ///     if worker.running_for > Some(Duration::from_secs(60)) {
+    ///         eprintln!("worker {} is stuck on {:?}", worker.index, worker.job_name);
+    ///     }
+    /// }
+    /// ```
+    pub fn health(&self) -> Vec<WorkerHealth> {
+        let mut workers = self.inner.lock_workers();
+        self.inner.reap(&mut workers);
+
+        let mut health: Vec<_> = workers
+            .iter()
+            .map(|worker| worker.slot.health(worker.index))
+            .collect();
+
+        health.sort_by_key(|worker| worker.index);
+        health
+    }
+
+    /// Spawns `count` additional workers, returning an error if a worker
+    /// thread can't be spawned
+    ///
+    /// The workers which were spawned before [`ThreadPoolError::SpawnFailed`]
+    /// is returned are kept and counted by [`ThreadPool::size`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use threatpool::ThreadPool;
+    ///
+    /// let pool = ThreadPool::new(2);
+    ///
+    /// pool.add_workers(2).unwrap();
+    /// pool.remove_workers(3);
+    /// ```
+    pub fn add_workers(&self, count: usize) -> Result<(), ThreadPoolError> {
+        let mut workers = self.inner.lock_workers();
+
+        for _ in 0..count {
+            self.inner.spawn_worker(&mut workers).map_err(|_| ThreadPoolError::SpawnFailed)?;
+            self.inner.size.fetch_add(1, Ordering::SeqCst);
+        }
+
+        Ok(())
+    }
+
+    /// Stops and joins `count` workers
+    ///
+    /// Whichever workers pick up the stop request first exit, so this blocks
+    /// until enough of them have finished their current job.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this would remove every worker.
+    pub fn remove_workers(&self, count: usize) {
+        let mut workers = self.inner.lock_workers();
+        self.inner.reap(&mut workers);
+
+        let shared = &self.inner.shared;
+        assert!(shared.queue.retire(count), "a thread pool needs at least one worker");
+
+        let size = self.inner.size.load(Ordering::SeqCst);
+        self.inner.size.store(size.saturating_sub(count).max(1), Ordering::SeqCst);
+
+        let mut exited = shared.exited.lock().unwrap_or_else(PoisonError::into_inner);
+
+        for _ in 0..count {
+            while exited.is_empty() {
+                exited = shared.worker_exited.wait(exited).unwrap_or_else(PoisonError::into_inner);
+            }
+
+            let index = exited.pop().unwrap();
+
+            if let Some(position) = workers.iter().position(|worker| worker.index == index) {
+                let mut worker = workers.remove(position);
+
+                if let Some(thread) = worker.thread.take() {
+                    join_thread(thread);
+                }
+            }
+        }
+    }
+
+    /// Shuts the pool down, blocking until every worker has finished
+    ///
+    /// Jobs which were already queued are executed before the workers exit.
+    /// This shuts down the pool shared by all clones of this handle.
+    /// Calling `execute` after the pool has been shut down is a logic error.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use threatpool::ThreadPool;
+    ///
+    /// let pool = ThreadPool::new(4);
+    ///
+    /// pool.execute(|| {
+    ///     println!("Hello, World");
+    /// });
+    ///
+    /// pool.shutdown();
+    /// ```
+    pub fn shutdown(self) {
+        self.inner.stop();
+        self.inner.join_workers();
+    }
+
+    /// Shuts the pool down like [`ThreadPool::shutdown`], but waits at most
+    /// `timeout` for the threads to finish
+    ///
+    /// Since threads can't be killed, the handles of the threads which are
+    /// still running after the timeout are returned instead, e.g. because a
+    /// job hangs. They finish in the background unless joined.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use threatpool::ThreadPool;
+    ///
+    /// let pool = ThreadPool::new(4);
+    /// pool.execute(|| println!("Hello, World"));
+    ///
+    /// if let Err(stragglers) = pool.shutdown_timeout(Duration::from_secs(1)) {
+    ///     eprintln!("{} threads are still running", stragglers.len());
+    /// }
+    /// ```
+    pub fn shutdown_timeout(self, timeout: Duration) -> Result<(), Vec<thread::JoinHandle<()>>> {
+        /// How often the threads are checked for having finished
+        const POLL_INTERVAL: Duration = Duration::from_millis(1);
+
+        let deadline = Instant::now() + timeout;
+
+        self.inner.stop();
+        let mut threads = self.inner.take_threads();
+
+        loop {
+            let (finished, running): (Vec<_>, Vec<_>) =
+                threads.into_iter().partition(thread::JoinHandle::is_finished);
+
+            for thread in finished {
+                let _ = thread.join();
+            }
+
+            if running.is_empty() {
+                return Ok(());
+            }
+
+            if Instant::now() >= deadline {
+                return Err(running);
+            }
+
+            threads = running;
+            thread::sleep(POLL_INTERVAL);
+        }
+    }
+
+    /// Shuts the pool down like [`ThreadPool::shutdown`], but returns the
+    /// queued jobs instead of executing them
+    ///
+    /// The jobs of the shared queue are returned first, in the order a single
+    /// worker would have executed them in, i.e. by priority and then by
+    /// [`QueueOrder`]. The jobs assigned to workers by [`Dispatch::RoundRobin`]
+    /// or queued locally by [`Scheduler::WorkStealing`] follow in no
+    /// particular order. The caller can run the jobs elsewhere or persist
+    /// them. The workers may still pick up some jobs while the pool shuts
+    /// down, those are executed and not returned. Delayed jobs which aren't
+    /// due yet are dropped like with `shutdown`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use threatpool::ThreadPool;
+    ///
+    /// let pool = ThreadPool::new(4);
+    /// pool.execute(|| println!("Hello, World"));
+    ///
+    /// for job in pool.shutdown_collecting() {
+    ///     job();
+    /// }
+    /// ```
+    pub fn shutdown_collecting(self) -> Vec<Box<dyn FnOnce() + Send + 'static>> {
+        self.inner.stop();
+
+        let shared = &self.inner.shared;
+        let jobs = shared.queue.take_all();
+
+        for job in &jobs {
+            shared.unqueue_bytes(job);
+        }
+
+        shared.reject_jobs(jobs.len());
+
+        self.inner.join_workers();
+
+        jobs.into_iter().map(|job| job.f).collect()
+    }
+
+    /// Shuts the pool down like [`ThreadPool::shutdown`] and builds a new one
+    /// with the same configuration
+    ///
+    /// The new pool is built from the [`ThreadPoolBuilder`] this pool was
+    /// built with, including its hooks, so workers added or removed later
+    /// don't carry over. Restarting a pool which was already shut down
+    /// through another handle only builds the new one. Other handles keep
+    /// referring to the old pool.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use threatpool::ThreadPoolBuilder;
+    ///
+    /// let pool = ThreadPoolBuilder::new().num_threads(4).build();
+    /// let pool = pool.restart();
+    ///
+    /// pool.execute(|| println!("Hello again"));
+    /// ```
+    pub fn restart(self) -> ThreadPool {
+        let builder = self.inner.builder.clone();
+        self.shutdown();
+
+        builder.build()
+    }
+
+    /// Shuts the pool down without waiting for the workers to finish
+    ///
+    /// The workers are detached and exit in the background once they are done
+    /// with the jobs which were already queued.
+    /// Calling `execute` after the pool has been shut down is a logic error.
+    pub fn shutdown_background(self) {
+        self.inner.stop();
+        self.inner.detach();
+    }
+}
+
+impl Inner {
+    /// Locks the workers, recovering them if a thread panicked while holding the lock
+    ///
+    /// The list is only modified by this module, which never leaves it
+    /// inconsistent across a panic, e.g. of a spawn handler.
+    fn lock_workers(&self) -> MutexGuard<'_, Vec<Worker>> {
+        self.workers.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Locks the timer, recovering it if a thread panicked while holding the lock
+    fn lock_timer(&self) -> MutexGuard<'_, Option<Timer>> {
+        self.timer.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Spawns a worker with the next index
+    fn spawn_worker(&self, workers: &mut Vec<Worker>) -> io::Result<()> {
+        let index = self.next_index.fetch_add(1, Ordering::SeqCst);
+        workers.push(Worker::new(index, Arc::clone(&self.shared), &self.builder)?);
+
+        Ok(())
+    }
+
+    /// Spawns a worker if the pool has shrunk and there are more jobs than workers
+    fn grow(&self) {
+        if self.builder.keep_alive.is_none() {
+            return;
+        }
+
+        let shared = &self.shared;
+        let jobs = shared.active.load(Ordering::SeqCst) + shared.queued.load(Ordering::SeqCst);
+
+        if jobs <= shared.queue.workers() {
+            return;
+        }
+
+        let mut workers = self.lock_workers();
+        self.reap(&mut workers);
+
+        // The existing workers still make progress if the pool can't grow
+        if shared.queue.workers() < self.size.load(Ordering::SeqCst) {
+            let _ = self.spawn_worker(&mut workers);
+        }
+    }
+
+    /// Joins the workers which have exited after idling for the keep-alive
+    fn reap(&self, workers: &mut Vec<Worker>) {
+        for index in self.shared.expired.lock().unwrap_or_else(PoisonError::into_inner).drain(..) {
+            if let Some(position) = workers.iter().position(|worker| worker.index == index) {
+                let mut worker = workers.remove(position);
+
+                if let Some(thread) = worker.thread.take() {
+                    join_thread(thread);
+                }
+            }
+        }
+    }
+
+    /// Closes the queue, so the workers exit once it is empty
+    fn stop(&self) {
+        if let Some(timer) = &*self.lock_timer() {
+            timer.close();
+        }
+
+        self.shared.queue.close();
+        self.blocking.close();
+        self.shared.advance(PoolState::ShuttingDown);
+    }
+
+    /// Takes the handles of the timer, the blocking lane and the workers, so
+    /// they are no longer joined on drop
+    fn take_threads(&self) -> Vec<thread::JoinHandle<()>> {
+        let mut threads = self.blocking.take_threads();
+        threads.extend(self.lock_timer().as_mut().and_then(Timer::take_thread));
+
+        for worker in self.lock_workers().iter_mut() {
+            threads.extend(worker.thread.take());
+        }
+
+        threads
+    }
+
+    /// Detaches the threads of the timer, the blocking lane and the workers
+    fn detach(&self) {
+        // Dropping the timer detaches its thread
+        self.lock_timer().take();
+        self.blocking.detach();
+
+        for worker in self.lock_workers().iter_mut() {
+            worker.thread.take();
+        }
+    }
+
+    fn join_workers(&self) {
+        if let Some(timer) = &mut *self.lock_timer() {
+            timer.join();
+        }
+
+        self.blocking.join();
+
+        for worker in self.lock_workers().iter_mut() {
+            if let Some(thread) = worker.thread.take() {
+                join_thread(thread);
+            }
+        }
+    }
+}
+
+impl Default for ThreadPool {
+    /// Creates a pool with one worker per available CPU
+    ///
+    /// Falls back to a single worker if the available parallelism can't be
+    /// determined.
+    fn default() -> Self {
+        ThreadPoolBuilder::new().build()
+    }
+}
+
+/// Shuts down and joins the workers of a pool which failed to build, so
+/// that no threads are leaked
+fn shut_down_spawned(shared: &Shared, workers: Vec<Worker>) {
+    shared.queue.close();
+
+    for mut worker in workers {
+        if let Some(thread) = worker.thread.take() {
+            join_thread(thread);
+        }
+    }
+}
+
+/// Waits for a thread to exit, tolerating a thread which panicked
+///
+/// The current thread is skipped, which happens when the last handle to a
+/// pool is dropped by one of its own jobs. That thread exits on its own once
+/// the job returns.
+fn join_thread(thread: thread::JoinHandle<()>) {
+    if thread.thread().id() != thread::current().id() {
+        let _ = thread.join();
+    }
+}
+
+/// The number of available CPUs or 1 if it can't be determined
+fn default_size() -> usize {
+    thread::available_parallelism().map(|n| n.get()).unwrap_or(1)
+}
+
+struct Worker {
+    index: usize,
+
+    /// The job the worker is running, shared with its thread
+    slot: Arc<WorkerSlot>,
+
+    thread: Option<thread::JoinHandle<()>>,
+}
+
+impl Worker {
+    /// Spawns a worker, unregistering it again if its thread can't be spawned
+    fn new(index: usize, shared: Arc<Shared>, builder: &ThreadPoolBuilder) -> io::Result<Worker> {
+        let prefix = builder
+            .thread_name_prefix
+            .as_deref()
+            .unwrap_or("threatpool-worker");
+
+        let mut thread = thread::Builder::new().name(format!("{prefix}-{index}"));
+
+        if let Some(stack_size) = builder.stack_size {
+            thread = thread.stack_size(stack_size);
+        }
+
+        let init = builder.worker_init.clone();
+        let priority = builder.thread_priority;
+
+        #[cfg(feature = "affinity")]
+        let core = builder
+            .cores
+            .as_ref()
+            .filter(|cores| !cores.is_empty())
+            .map(|cores| cores[index % cores.len()]);
+
+        let slot = Arc::new(WorkerSlot::default());
+        let worker_slot = Arc::clone(&slot);
+
+        // Registering before spawning keeps the number of workers in the queue accurate
+        shared.queue.register(index);
+        let registered = Arc::clone(&shared);
+
+        let main = move || {
+            let slot = worker_slot;
+            CURRENT_WORKER.with(|current| current.set(Some((shared.id, index))));
+
+            #[cfg(feature = "affinity")]
+            if let Some(core) = core {
+                affinity::pin_current_thread(core);
+            }
+
+            if let Some(priority) = priority {
+                priority::set_current_thread_priority(priority);
+            }
+
+            if let Some(init) = init {
+                init();
+            }
+
+            // Returns an error once the pool has been shut down and the queue is
+            // empty, if this worker has been asked to retire or if it has expired
+            let exit = loop {
+                // Incrementing and decrementing around every pop keeps the
+                // count from going negative, whichever way the pop returns
+                shared.idle_workers.fetch_add(1, Ordering::SeqCst);
+                let job = shared.queue.pop(index);
+                shared.idle_workers.fetch_sub(1, Ordering::SeqCst);
+
+                match job {
+                    Ok(job) => shared.run(job, Some(&slot)),
+                    Err(exit) => break exit,
+                }
+            };
+
+            shared.queue.unregister(index);
+
+            if exit == Exit::Closed && shared.queue.workers() == 0 {
+                shared.advance(PoolState::Stopped);
+            }
+
+            if exit == Exit::Expired {
+                let mut expired = shared.expired.lock().unwrap_or_else(PoisonError::into_inner);
+                expired.push(index);
+            } else {
+                let mut exited = shared.exited.lock().unwrap_or_else(PoisonError::into_inner);
+                exited.push(index);
+                shared.worker_exited.notify_all();
+            }
+        };
+
+        let thread = match &builder.spawn_handler {
+            Some(spawn_handler) => spawn_handler(Box::new(main)),
+
+            None => thread.spawn(main).map_err(|err| {
+                // Nobody is going to run the broadcast jobs meant for this worker
+                for job in registered.queue.forget(index) {
+                    registered.unqueue_bytes(&job);
+                    registered.reject_jobs(1);
+                }
+
+                err
+            })?,
+        };
+
+        Ok(Self {
+            index,
+            slot,
+            thread: Some(thread),
+        })
+    }
+}
+
+impl Drop for Inner {
+    fn drop(&mut self) {
+        self.stop();
+
+        match self.builder.drop_behavior {
+            DropBehavior::Join => self.join_workers(),
+            DropBehavior::Detach => self.detach(),
+        }
+    }
+}
+
+#[test]
+fn main() {
+    let pool = ThreadPool::new(10);
+
+    for _ in 0..10000000 {
+        pool.execute(|| {
+            println!("Hello, World!"); // Simulates some heavy task
+        });
+    }
+}
+
+#[test]
+fn execute_with_result() {
+    let pool = ThreadPool::new(4);
+
+    let handles: Vec<_> = (0..100)
+        .map(|i| pool.execute_with_result(move || i * i))
+        .collect();
+
+    for (i, handle) in handles.into_iter().enumerate() {
+        assert_eq!(handle.join().unwrap(), i * i);
+    }
+
+    // Dropping a handle must not block or break the worker
+    drop(pool.execute_with_result(|| 1));
+    assert_eq!(pool.execute_with_result(|| 2).join().unwrap(), 2);
+}
+
+#[test]
+fn shutdown() {
+    let counter = Arc::new(AtomicUsize::new(0));
+
+    let pool = ThreadPool::new(4);
+    for _ in 0..100 {
+        let counter = Arc::clone(&counter);
+        pool.execute(move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+        });
+    }
+    pool.shutdown();
+
+    assert_eq!(counter.load(Ordering::SeqCst), 100);
+
+    let pool = ThreadPool::new(4);
+    pool.execute(|| thread::sleep(std::time::Duration::from_millis(10)));
+    pool.shutdown_background();
+}
+
+#[test]
+fn survives_panicking_job() {
+    let pool = ThreadPool::new(1);
+
+    pool.execute(|| panic!("Simulates a failing task"));
+
+    assert_eq!(pool.execute_with_result(|| 42).join().unwrap(), 42);
+}
+
+#[test]
+fn builder() {
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(2)
+        .thread_name_prefix("builder-test")
+        .stack_size(4 * 1024 * 1024)
+        .build();
+
+    let name = pool
+        .execute_with_result(|| thread::current().name().map(String::from))
+        .join()
+        .unwrap();
+
+    assert!(name.unwrap().starts_with("builder-test-"));
+}
+
+#[test]
+fn worker_names() {
+    let pool = ThreadPool::new(1);
+
+    let name = pool
+        .execute_with_result(|| thread::current().name().map(String::from))
+        .join()
+        .unwrap();
+
+    assert_eq!(name.as_deref(), Some("threatpool-worker-0"));
+}
+
+#[test]
+fn default() {
+    let pool = ThreadPool::default();
+
+    assert!(!pool.inner.workers.lock().unwrap().is_empty());
+}
+
+#[test]
+fn clone() {
+    let counter = Arc::new(AtomicUsize::new(0));
+    let pool = ThreadPool::new(4);
+
+    let producers: Vec<_> = (0..2)
+        .map(|_| {
+            let pool = pool.clone();
+            let counter = Arc::clone(&counter);
+
+            thread::spawn(move || {
+                for _ in 0..500 {
+                    let counter = Arc::clone(&counter);
+                    pool.execute(move || {
+                        counter.fetch_add(1, Ordering::SeqCst);
+                    });
+                }
+            })
+        })
+        .collect();
+
+    for producer in producers {
+        producer.join().unwrap();
+    }
+
+    // The producers' clones are gone, so this drop is the one shutting down
+    drop(pool);
+
+    assert_eq!(counter.load(Ordering::SeqCst), 1000);
+}
+
+#[test]
+fn join() {
+    let counter = Arc::new(AtomicUsize::new(0));
+    let pool = ThreadPool::new(8);
+
+    for _ in 0..1000 {
+        let counter = Arc::clone(&counter);
+        pool.execute(move || {
+            thread::sleep(std::time::Duration::from_millis(1));
+            counter.fetch_add(1, Ordering::SeqCst);
+        });
+    }
+
+    pool.join();
+    assert_eq!(counter.load(Ordering::SeqCst), 1000);
+
+    // The pool can be reused after joining
+    pool.execute(|| {});
+    pool.join();
+}
+
+#[test]
+fn try_execute() {
+    use std::sync::atomic::AtomicBool;
+
+    let pool = ThreadPool::new(2);
+    assert!(pool.try_execute(|| {}).is_ok());
+
+    let other = pool.clone();
+    pool.shutdown();
+
+    let ran = Arc::new(AtomicBool::new(false));
+    let flag = Arc::clone(&ran);
+
+    let err = other
+        .try_execute(move || flag.store(true, Ordering::SeqCst))
+        .unwrap_err();
+    assert!(!ran.load(Ordering::SeqCst));
+
+    // The rejected job is handed back intact
+    (err.into_inner())();
+    assert!(ran.load(Ordering::SeqCst));
+}
+
+#[test]
+fn bounded_queue() {
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(1)
+        .queue_capacity(1)
+        .build();
+
+    // Occupy the only worker
+    let release = block_worker(&pool);
+
+    // Fill the only slot in the queue
+    pool.try_execute_nonblocking(|| {}).unwrap();
+
+    assert!(matches!(
+        pool.try_execute_nonblocking(|| {}),
+        Err(TryExecuteError::Full(_))
+    ));
+
+    release.send(()).unwrap();
+    pool.join();
+
+    assert!(pool.try_execute_nonblocking(|| {}).is_ok());
+}
+
+#[test]
+fn execute_with_priority() {
+    let pool = ThreadPool::new(1);
+    let log = Arc::new(Mutex::new(Vec::new()));
+
+    // Keep the only worker busy while the other jobs are queued
+    let release = block_worker(&pool);
+
+    for (priority, name) in [(0, "low"), (10, "high"), (5, "medium")] {
+        let log = Arc::clone(&log);
+        pool.execute_with_priority(priority, move || log.lock().unwrap().push(name));
+    }
+
+    release.send(()).unwrap();
+
+    pool.join();
+    assert_eq!(*log.lock().unwrap(), ["high", "medium", "low"]);
+}
+
+#[test]
+fn scope() {
+    let pool = ThreadPool::new(4);
+
+    let numbers: Vec<usize> = (1..=1000).collect();
+    let sum = AtomicUsize::new(0);
+
+    pool.scope(|scope| {
+        for chunk in numbers.chunks(100) {
+            let sum = &sum;
+            scope.spawn(move || {
+                sum.fetch_add(chunk.iter().sum(), Ordering::SeqCst);
+            });
+        }
+    });
+
+    assert_eq!(sum.into_inner(), 500500);
+}
+
+#[test]
+fn scope_propagates_panics() {
+    let pool = ThreadPool::new(2);
+
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        pool.scope(|scope| {
+            scope.spawn(|| panic!("Simulates a failing task"));
+        });
+    }));
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn panic_handler() {
+    use std::sync::atomic::AtomicBool;
+
+    let panicked = Arc::new(AtomicBool::new(false));
+    let flag = Arc::clone(&panicked);
+
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(1)
+        .panic_handler(move |payload| {
+            assert_eq!(payload.downcast_ref::<&str>(), Some(&"Simulates a failing task"));
+            flag.store(true, Ordering::SeqCst);
+        })
+        .build();
+
+    pool.execute(|| panic!("Simulates a failing task"));
+    pool.join();
+
+    assert!(panicked.load(Ordering::SeqCst));
+}
+
+#[test]
+fn worker_init() {
+    use std::collections::HashSet;
+
+    let ids = Arc::new(Mutex::new(HashSet::new()));
+    let init_ids = Arc::clone(&ids);
+
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(4)
+        .worker_init(move || {
+            init_ids.lock().unwrap().insert(thread::current().id());
+        })
+        .build();
+
+    pool.shutdown();
+
+    assert_eq!(ids.lock().unwrap().len(), 4);
+}
+
+#[test]
+fn map() {
+    let pool = ThreadPool::new(4);
+
+    let squares = pool.map(0..100, |i| i * i);
+    assert_eq!(squares, (0..100).map(|i| i * i).collect::<Vec<_>>());
+
+    let empty = pool.map(Vec::<usize>::new(), |i| i);
+    assert!(empty.is_empty());
+}
+
+#[test]
+fn execute_after() {
+    use std::sync::atomic::AtomicBool;
+
+    let pool = ThreadPool::new(2);
+    let ran = Arc::new(AtomicBool::new(false));
+
+    let flag = Arc::clone(&ran);
+    pool.execute_after(Duration::from_millis(50), move || {
+        flag.store(true, Ordering::SeqCst);
+    });
+
+    thread::sleep(Duration::from_millis(10));
+    assert!(!ran.load(Ordering::SeqCst));
+
+    thread::sleep(Duration::from_millis(90));
+    assert!(ran.load(Ordering::SeqCst));
+
+    // Jobs which aren't due yet are dropped on shutdown
+    pool.execute_after(Duration::from_secs(60), || unreachable!());
+    pool.shutdown();
+}
+
+#[test]
+fn execute_cancellable() {
+    use std::sync::atomic::AtomicBool;
+
+    let pool = ThreadPool::new(1);
+    let ran = Arc::new(AtomicBool::new(false));
+
+    pool.execute(|| thread::sleep(Duration::from_millis(50)));
+
+    let flag = Arc::clone(&ran);
+    let token = pool.execute_cancellable(move || flag.store(true, Ordering::SeqCst));
+
+    token.cancel();
+    assert!(token.is_cancelled());
+
+    pool.join();
+    assert!(!ran.load(Ordering::SeqCst));
+}
+
+#[test]
+fn execute_all() {
+    let pool = ThreadPool::new(4);
+    let counter = Arc::new(AtomicUsize::new(0));
+
+    let jobs: Vec<_> = (0..50)
+        .map(|_| {
+            let counter = Arc::clone(&counter);
+            move || {
+                counter.fetch_add(1, Ordering::SeqCst);
+            }
+        })
+        .collect();
+
+    pool.execute_all(jobs);
+    pool.join();
+
+    assert_eq!(counter.load(Ordering::SeqCst), 50);
+}
+
+#[test]
+fn add_and_remove_workers() {
+    let pool = ThreadPool::new(2);
+    let counter = Arc::new(AtomicUsize::new(0));
+
+    pool.add_workers(3).unwrap();
+    assert_eq!(pool.inner.workers.lock().unwrap().len(), 5);
+
+    for _ in 0..100 {
+        let counter = Arc::clone(&counter);
+        pool.execute(move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+        });
+    }
+    pool.join();
+    assert_eq!(counter.load(Ordering::SeqCst), 100);
+
+    pool.remove_workers(3);
+    assert_eq!(pool.inner.workers.lock().unwrap().len(), 2);
+
+    assert_eq!(pool.execute_with_result(|| 42).join().unwrap(), 42);
+}
+
+#[test]
+fn stats() {
+    use std::sync::Barrier;
+
+    let pool = ThreadPool::new(4);
+    let barrier = Arc::new(Barrier::new(5));
+
+    for _ in 0..4 {
+        let barrier = Arc::clone(&barrier);
+        pool.execute(move || {
+            barrier.wait();
+        });
+    }
+
+    // Queued behind the blocked jobs
+    pool.execute(|| {});
+    pool.execute(|| {});
+
+    while pool.active_count() < 4 {
+        thread::sleep(Duration::from_millis(1));
+    }
+
+    let expected = PoolStats {
+        active: 4,
+        queued: 2,
+        completed: 0,
+        submitted: 6,
+        failed: 0,
+        idle: 0,
+    };
+
+    assert_eq!(pool.stats(), expected);
+
+    barrier.wait();
+    pool.join();
+
+    // The workers become idle only after `join` has returned
+    let stats = pool.stats();
+    assert_eq!((stats.active, stats.queued, stats.completed), (0, 0, 6));
+}
+
+#[test]
+fn drain() {
+    let pool = ThreadPool::new(2);
+    let counter = Arc::new(AtomicUsize::new(0));
+
+    for _ in 0..10 {
+        let counter = Arc::clone(&counter);
+        pool.execute(move || {
+            thread::sleep(Duration::from_millis(5));
+            counter.fetch_add(1, Ordering::SeqCst);
+        });
+    }
+
+    pool.drain();
+
+    assert_eq!(counter.load(Ordering::SeqCst), 10);
+    assert!(pool.try_execute(|| {}).is_err());
+
+    pool.reopen();
+    assert_eq!(pool.execute_with_result(|| 42).join().unwrap(), 42);
+}
+
+#[test]
+fn throughput() {
+    let pool = ThreadPool::new(8);
+    let counter = Arc::new(AtomicUsize::new(0));
+
+    let producers: Vec<_> = (0..4)
+        .map(|_| {
+            let pool = pool.clone();
+            let counter = Arc::clone(&counter);
+
+            thread::spawn(move || {
+                for _ in 0..250_000 {
+                    let counter = Arc::clone(&counter);
+                    pool.execute(move || {
+                        counter.fetch_add(1, Ordering::Relaxed);
+                    });
+                }
+            })
+        })
+        .collect();
+
+    for producer in producers {
+        producer.join().unwrap();
+    }
+
+    pool.join();
+    assert_eq!(counter.load(Ordering::SeqCst), 1_000_000);
+}
+
+#[test]
+fn work_stealing() {
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(4)
+        .scheduler(Scheduler::WorkStealing)
+        .build();
+
+    let counter = Arc::new(AtomicUsize::new(0));
+
+    // Every job spawns more jobs onto its worker's local queue
+    for _ in 0..10 {
+        let inner_pool = pool.clone();
+        let counter = Arc::clone(&counter);
+
+        pool.execute(move || {
+            for _ in 0..100 {
+                let counter = Arc::clone(&counter);
+                inner_pool.execute(move || {
+                    counter.fetch_add(1, Ordering::SeqCst);
+                });
+            }
+        });
+    }
+
+    pool.join();
+    assert_eq!(counter.load(Ordering::SeqCst), 1000);
+
+    // Jobs left on the local queue of a removed worker are moved, not lost
+    pool.remove_workers(3);
+    assert_eq!(pool.execute_with_result(|| 42).join().unwrap(), 42);
+
+    // Jobs submitted from a worker still count towards the capacity
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(1)
+        .queue_capacity(1)
+        .scheduler(Scheduler::WorkStealing)
+        .build();
+
+    let inner_pool = pool.clone();
+    let result = pool.execute_with_result(move || {
+        inner_pool.try_execute_nonblocking(|| {}).unwrap();
+        inner_pool.try_execute_nonblocking(|| {}).unwrap_err().error()
+    });
+
+    assert_eq!(result.join().unwrap(), ThreadPoolError::QueueFull);
+}
+
+#[test]
+fn join_timeout() {
+    let pool = ThreadPool::new(1);
+
+    let handle = pool.execute_with_result(|| {
+        thread::sleep(Duration::from_millis(200));
+        1
+    });
+
+    assert!(matches!(
+        handle.join_timeout(Duration::from_millis(10)),
+        Err(JoinTimeout::Timeout)
+    ));
+
+    let handle = pool.execute_with_result(|| 2);
+    assert_eq!(handle.join_timeout(Duration::from_secs(5)).unwrap(), 2);
+}
+
+#[test]
+fn global_pool() {
+    let (sender, receiver) = mpsc::channel();
+
+    let threads: Vec<_> = (0..4)
+        .map(|i| {
+            let sender = sender.clone();
+            thread::spawn(move || {
+                for j in 0..10 {
+                    let sender = sender.clone();
+                    spawn(move || sender.send(i * 10 + j).unwrap());
+                }
+            })
+        })
+        .collect();
+
+    for thread in threads {
+        thread.join().unwrap();
+    }
+
+    drop(sender);
+
+    let mut results: Vec<i32> = receiver.iter().collect();
+    results.sort_unstable();
+    assert_eq!(results, (0..40).collect::<Vec<_>>());
+    assert!(std::ptr::eq(global(), global()));
+}
+
+#[test]
+fn broadcast() {
+    use std::collections::HashSet;
+
+    let pool = ThreadPool::new(4);
+    let ids = Arc::new(Mutex::new(Vec::new()));
+
+    for _ in 0..3 {
+        let recorded = Arc::clone(&ids);
+        pool.broadcast(move || recorded.lock().unwrap().push(thread::current().id()));
+        pool.join();
+
+        let ids = std::mem::take(&mut *ids.lock().unwrap());
+        assert_eq!(ids.len(), 4);
+        assert_eq!(ids.into_iter().collect::<HashSet<_>>().len(), 4);
+    }
+}
+
+/// Broadcasts a job recording the names of the threads running it while
+/// every worker of `pool` is busy, returning the names and a barrier which
+/// releases the workers
+#[cfg(test)]
+fn broadcast_while_busy(pool: &ThreadPool) -> (Arc<Mutex<Vec<String>>>, Arc<std::sync::Barrier>) {
+    let barrier = Arc::new(std::sync::Barrier::new(pool.size() + 1));
+
+    for _ in 0..pool.size() {
+        let barrier = Arc::clone(&barrier);
+        pool.execute(move || {
+            barrier.wait();
+        });
+    }
+
+    while pool.active_count() < pool.size() {
+        thread::yield_now();
+    }
+
+    let names = Arc::new(Mutex::new(Vec::new()));
+    let recorded = Arc::clone(&names);
+
+    pool.broadcast(move || {
+        let name = thread::current().name().unwrap().to_owned();
+        recorded.lock().unwrap().push(name);
+    });
+
+    (names, barrier)
+}
+
+/// Occupies one worker of `pool` until the returned sender is used or dropped
+#[cfg(test)]
+fn block_worker(pool: &ThreadPool) -> mpsc::Sender<()> {
+    let (release, released) = mpsc::channel::<()>();
+    let (started_sender, started) = mpsc::channel();
+
+    pool.execute(move || {
+        started_sender.send(()).unwrap();
+        let _ = released.recv();
+    });
+    started.recv().unwrap();
+
+    release
+}
+
+#[test]
+fn broadcast_join_and_help() {
+    use std::collections::HashSet;
+
+    let pool = ThreadPool::new(4);
+    let (names, barrier) = broadcast_while_busy(&pool);
+
+    let releaser = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(50));
+        barrier.wait();
+    });
+
+    // The copies are pinned to the workers, so the caller can't take one
+    pool.join_and_help();
+    releaser.join().unwrap();
+
+    let names = names.lock().unwrap();
+    assert!(names.iter().all(|name| name.starts_with("threatpool-worker")));
+    assert_eq!(names.iter().collect::<HashSet<_>>().len(), 4);
+}
+
+#[test]
+fn broadcast_remove_workers() {
+    use std::collections::HashSet;
+
+    let pool = ThreadPool::new(4);
+    let (names, barrier) = broadcast_while_busy(&pool);
+
+    let releaser = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(50));
+        barrier.wait();
+    });
+
+    // The retiring worker runs its copy first
+    pool.remove_workers(1);
+    releaser.join().unwrap();
+    pool.join();
+
+    assert_eq!(pool.size(), 3);
+    assert_eq!(names.lock().unwrap().iter().collect::<HashSet<_>>().len(), 4);
+}
+
+#[test]
+fn try_execute_nonblocking_counters() {
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(1)
+        .queue_capacity(1)
+        .build();
+
+    let (sender, receiver) = mpsc::channel::<()>();
+    pool.execute(move || {
+        let _ = receiver.recv();
+    });
+
+    while pool.active_count() == 0 {
+        thread::yield_now();
+    }
+
+    assert!(pool.try_execute_nonblocking(|| {}).is_ok());
+    assert!(pool.try_execute_nonblocking(|| {}).is_err());
+    let expected = PoolStats {
+        active: 1,
+        queued: 1,
+        completed: 0,
+        submitted: 2,
+        failed: 0,
+        idle: 0,
+    };
+
+    assert_eq!(pool.stats(), expected);
+
+    drop(sender);
+    pool.join();
+
+    let stats = pool.stats();
+    assert_eq!((stats.active, stats.queued, stats.completed), (0, 0, 2));
+}
+
+#[test]
+fn keep_alive() {
+    use std::sync::Barrier;
+
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(4)
+        .min_threads(1)
+        .keep_alive(Duration::from_millis(50))
+        .build();
+
+    let workers = || pool.size();
+
+    for _ in 0..20 {
+        pool.execute(|| thread::sleep(Duration::from_millis(10)));
+    }
+
+    pool.join();
+
+    let start = Instant::now();
+    while workers() > 1 && start.elapsed() < Duration::from_secs(5) {
+        thread::sleep(Duration::from_millis(10));
+    }
+
+    assert_eq!(workers(), 1);
+
+    // Every job waits for all others, so this only finishes if the pool grows back
+    let barrier = Arc::new(Barrier::new(5));
+
+    for _ in 0..4 {
+        let barrier = Arc::clone(&barrier);
+        pool.execute(move || {
+            barrier.wait();
+        });
+    }
+
+    barrier.wait();
+    assert_eq!(workers(), 4);
+}
+
+#[test]
+fn spawn_blocking() {
+    let pool = ThreadPool::new(2);
+
+    let sum = futures::executor::block_on(async {
+        let first = pool.spawn_blocking(|| {
+            thread::sleep(Duration::from_millis(50));
+            1
+        });
+
+        let second = pool.spawn_blocking(|| 2);
+
+        first.await + second.await
+    });
+
+    assert_eq!(sum, 3);
+}
+
+#[test]
+fn survives_poisoned_queue() {
+    struct PanicOnClone;
+
+    impl Clone for PanicOnClone {
+        fn clone(&self) -> Self {
+            panic!("cloning is not allowed");
+        }
+    }
+
+    let pool = ThreadPool::new(4);
+    let shared = Arc::clone(&pool.inner.shared);
+
+    // The copies are created while the queue is locked, so this poisons it
+    let value = PanicOnClone;
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        pool.broadcast(move || {
+            let _value = &value;
+        });
+    }));
+    assert!(result.is_err());
+
+    let handle = pool.execute_with_result(|| 42);
+    assert_eq!(handle.join().unwrap(), 42);
+
+    // Dropping the pool joins the workers, which fails if one of them panicked
+    drop(pool);
+    assert_eq!(shared.exited.lock().unwrap().len(), 4);
+}
+
+#[test]
+fn join_from_worker() {
+    let pool = ThreadPool::new(2);
+    let other = ThreadPool::new(1);
+
+    let handle = pool.execute_with_result({
+        let pool = pool.clone();
+        move || {
+            // Joining another pool is fine
+            other.join();
+            panic::catch_unwind(AssertUnwindSafe(|| pool.join())).is_err()
+        }
+    });
+
+    assert!(handle.join().unwrap());
+    pool.join();
+}
+
+#[test]
+fn execute_named() {
+    let (sender, receiver) = mpsc::channel();
+
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(1)
+        .panic_handler(move |_| sender.send(current_job_name()).unwrap())
+        .build();
+
+    pool.execute_named("exploding", || panic!("boom"));
+    pool.execute(|| panic!("boom"));
+
+    assert_eq!(receiver.recv().unwrap().as_deref(), Some("exploding"));
+    assert_eq!(receiver.recv().unwrap(), None);
+}
+
+#[test]
+fn for_each() {
+    let pool = ThreadPool::new(4);
+    let processed = Arc::new(Mutex::new(Vec::new()));
+
+    let recorded = Arc::clone(&processed);
+    pool.for_each(0..100, move |i| recorded.lock().unwrap().push(i));
+
+    let mut processed = processed.lock().unwrap().clone();
+    processed.sort_unstable();
+    assert_eq!(processed, (0..100).collect::<Vec<_>>());
+}
+
+#[test]
+fn reduce() {
+    let pool = ThreadPool::new(4);
+
+    assert_eq!(pool.reduce(1..=1000, 0, |a, b| a + b), 500500);
+    assert_eq!(pool.reduce(1..=2, 0, |a, b| a + b), 3);
+    assert_eq!(pool.reduce(Vec::new(), 7, |a, b| a + b), 7);
+
+    // Chunks are combined in order
+    let words = ["a", "b", "c", "d", "e", "f"].map(String::from);
+    assert_eq!(pool.reduce(words, String::new(), |a, b| a + &b), "abcdef");
+}
+
+#[test]
+fn thread_pool_error() {
+    let pool = ThreadPool::new(2);
+    let other = pool.clone();
+
+    pool.drain();
+    let err = other.try_execute(|| {}).unwrap_err();
+    assert_eq!(err.error(), ThreadPoolError::Draining);
+
+    other.reopen();
+    pool.shutdown();
+
+    let err = other.try_execute(|| {}).unwrap_err();
+    assert_eq!(ThreadPoolError::from(err), ThreadPoolError::PoolShutdown);
+
+    let err = other.try_execute_nonblocking(|| {}).unwrap_err();
+    assert!(matches!(err, TryExecuteError::Disconnected(_)));
+    assert_eq!(err.to_string(), "the thread pool has been shut down");
+}
+
+#[test]
+fn idle_count() {
+    let pool = ThreadPool::new(4);
+
+    let start = Instant::now();
+    while pool.idle_count() < 4 && start.elapsed() < Duration::from_secs(5) {
+        thread::sleep(Duration::from_millis(1));
+    }
+
+    assert_eq!(pool.idle_count(), 4);
+
+    let (sender, receiver) = mpsc::channel::<()>();
+    pool.execute(move || {
+        let _ = receiver.recv();
+    });
+
+    while pool.active_count() == 0 {
+        thread::yield_now();
+    }
+
+    assert_eq!(pool.idle_count(), 3);
+
+    drop(sender);
+    pool.shutdown();
+}
+
+#[test]
+fn size() {
+    let pool = ThreadPool::new(2);
+    assert_eq!(pool.size(), 2);
+
+    pool.add_workers(3).unwrap();
+    assert_eq!(pool.size(), 5);
+
+    pool.remove_workers(4);
+    assert_eq!(pool.size(), 1);
+}
+
+#[cfg(all(feature = "affinity", target_os = "linux"))]
+#[test]
+fn pin_to_cores() {
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(1)
+        .pin_to_cores(vec![0])
+        .build();
+
+    let cores = pool.execute_with_result(|| {
+        // SAFETY: `set` is a valid, zeroed `cpu_set_t` which outlives the calls
+        unsafe {
+            let mut set: libc::cpu_set_t = std::mem::zeroed();
+            libc::sched_getaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &mut set);
+            (libc::CPU_ISSET(0, &set), libc::CPU_COUNT(&set))
+        }
+    });
+
+    assert_eq!(cores.join().unwrap(), (true, 1));
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn thread_priority() {
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(1)
+        .thread_priority(Priority::Low)
+        .build();
+
+    let nice = pool.execute_with_result(|| {
+        // SAFETY: Both calls only take plain integers
+        unsafe {
+            let tid = libc::syscall(libc::SYS_gettid) as libc::id_t;
+            libc::getpriority(libc::PRIO_PROCESS, tid)
+        }
+    });
+
+    // Lowering the priority always works, but may start from a non-zero nice value
+    assert!(nice.join().unwrap() >= 10);
+}
+
+#[test]
+fn state() {
+    let pool = ThreadPool::new(2);
+    let other = pool.clone();
+    assert_eq!(pool.state(), PoolState::Running);
+
+    pool.drain();
+    assert_eq!(pool.state(), PoolState::Draining);
+
+    pool.reopen();
+    assert_eq!(pool.state(), PoolState::Running);
+
+    pool.shutdown();
+    assert_eq!(other.state(), PoolState::Stopped);
+
+    // Reopening doesn't bring a stopped pool back
+    other.reopen();
+    assert_eq!(other.state(), PoolState::Stopped);
+}
+
+#[test]
+fn rate_limit() {
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(4)
+        .rate_limit(5)
+        .build();
+
+    let started = Arc::new(Mutex::new(Vec::new()));
+
+    for _ in 0..10 {
+        let started = Arc::clone(&started);
+        pool.execute(move || started.lock().unwrap().push(Instant::now()));
+    }
+
+    pool.join();
+
+    let started = started.lock().unwrap();
+    let first = started.iter().min().unwrap();
+    let last = started.iter().max().unwrap();
+
+    // The first 5 start right away, the other 5 one every 200ms
+    assert!(last.duration_since(*first) >= Duration::from_millis(900));
+}
+
+#[test]
+fn on_complete() {
+    let completed = Arc::new(AtomicUsize::new(0));
+    let counter = Arc::clone(&completed);
+
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(4)
+        .on_complete(move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+        })
+        .build();
+
+    for i in 0..20 {
+        pool.execute(move || assert!(i % 5 != 0));
+    }
+
+    pool.join();
+    assert_eq!(completed.load(Ordering::SeqCst), 20);
+}
+
+#[test]
+fn restart() {
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(2)
+        .thread_name_prefix("restarted")
+        .build();
+
+    let other = pool.clone();
+    pool.shutdown();
+    assert_eq!(other.state(), PoolState::Stopped);
+
+    let pool = other.restart();
+    assert_eq!(pool.state(), PoolState::Running);
+    assert_eq!(pool.size(), 2);
+
+    let name = pool
+        .execute_with_result(|| thread::current().name().map(String::from))
+        .join()
+        .unwrap();
+
+    assert!(name.unwrap().starts_with("restarted-"));
+}
+
+#[test]
+fn pipe_to() {
+    let first = ThreadPool::new(2);
+    let second = ThreadPool::new(2);
+    let (sender, receiver) = mpsc::channel();
+
+    for i in 0..10 {
+        let sender = sender.clone();
+        first.pipe_to(&second, move || i * i, move |square| {
+            sender.send(square + 1).unwrap();
+        });
+    }
+
+    drop(sender);
+
+    let mut results: Vec<i32> = receiver.iter().collect();
+    results.sort_unstable();
+    assert_eq!(results, (0..10).map(|i| i * i + 1).collect::<Vec<_>>());
+}
+
+#[test]
+fn is_idle() {
+    let pool = ThreadPool::new(2);
+    assert!(pool.is_idle());
+
+    let (sender, receiver) = mpsc::channel::<()>();
+    pool.execute(move || {
+        let _ = receiver.recv();
+    });
+    pool.execute(|| {});
+
+    assert!(!pool.is_idle());
+    assert!(pool.pending() >= 1);
+
+    drop(sender);
+
+    let start = Instant::now();
+    while !pool.is_idle() && start.elapsed() < Duration::from_secs(5) {
+        thread::yield_now();
+    }
+
+    assert!(pool.is_idle());
+    assert_eq!(pool.pending(), 0);
+}
+
+#[test]
+fn execute_blocking() {
+    let pool = ThreadPool::new(2);
+    let finished = Arc::new(AtomicUsize::new(0));
+
+    for _ in 0..8 {
+        let finished = Arc::clone(&finished);
+        pool.execute_blocking(move || {
+            thread::sleep(Duration::from_millis(300));
+            finished.fetch_add(1, Ordering::SeqCst);
+        });
+    }
+
+    // The workers aren't occupied by the blocking jobs
+    let start = Instant::now();
+    pool.execute_with_result(|| {}).join().unwrap();
+    assert!(start.elapsed() < Duration::from_millis(200));
+
+    pool.join();
+    assert_eq!(finished.load(Ordering::SeqCst), 8);
+
+    let (sender, receiver) = mpsc::channel();
+    pool.execute_blocking(move || {
+        sender.send(thread::current().name().map(String::from)).unwrap();
+    });
+
+    let name = receiver.recv().unwrap().unwrap();
+    assert!(name.starts_with("threatpool-blocking-"));
+}
+
+#[test]
+fn execute_blocking_spawn_failed() {
+    // The workers ignore the stack size, but the stack of a blocking thread can't be allocated
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(1)
+        .stack_size(usize::MAX)
+        .spawn_handler(thread::spawn)
+        .build();
+
+    let result = panic::catch_unwind(AssertUnwindSafe(|| pool.execute_blocking(|| {})));
+    assert!(result.is_err());
+
+    // The job wasn't left queued without a thread to run it
+    pool.join();
+    assert_eq!(pool.queued_count(), 0);
+    assert_eq!(pool.submitted_count(), 0);
+}
+
+#[test]
+fn health() {
+    let pool = ThreadPool::new(2);
+    let (sender, receiver) = mpsc::channel::<()>();
+
+    pool.execute_named("stuck", move || {
+        let _ = receiver.recv();
+    });
+
+    while pool.active_count() == 0 {
+        thread::yield_now();
+    }
+
+    thread::sleep(Duration::from_millis(10));
+
+    let health = pool.health();
+    assert_eq!(health.len(), 2);
+
+    let busy: Vec<_> = health.iter().filter(|worker| !worker.is_idle()).collect();
+    assert_eq!(busy.len(), 1);
+    assert_eq!(busy[0].job_name.as_deref(), Some("stuck"));
+    assert!(busy[0].running_for.unwrap() >= Duration::from_millis(10));
+
+    drop(sender);
+    pool.join();
+
+    assert!(pool.health().iter().all(WorkerHealth::is_idle));
+}
+
+#[test]
+fn execute_with_deadline() {
+    use std::sync::atomic::AtomicBool;
+
+    let pool = ThreadPool::new(1);
+    let stale = Arc::new(AtomicBool::new(false));
+    let fresh = Arc::new(AtomicBool::new(false));
+
+    pool.execute(|| thread::sleep(Duration::from_millis(100)));
+
+    let ran = Arc::clone(&stale);
+    pool.execute_with_deadline(Instant::now() + Duration::from_millis(10), move || {
+        ran.store(true, Ordering::SeqCst);
+    });
+
+    let ran = Arc::clone(&fresh);
+    pool.execute_with_deadline(Instant::now() + Duration::from_secs(60), move || {
+        ran.store(true, Ordering::SeqCst);
+    });
+
+    pool.join();
+
+    assert!(!stale.load(Ordering::SeqCst));
+    assert!(fresh.load(Ordering::SeqCst));
+    assert_eq!(pool.completed_count(), 2);
+}
+
+#[test]
+fn join_all_in_order() {
+    let pool = ThreadPool::new(4);
+
+    let handles = (0..50)
+        .map(|i| {
+            pool.execute_with_result(move || {
+                thread::sleep(Duration::from_millis(50 - i));
+                i
+            })
+        })
+        .collect();
+
+    assert_eq!(join_all(handles), (0..50).collect::<Vec<_>>());
+    assert!(join_all::<()>(Vec::new()).is_empty());
+}
+
+#[test]
+fn round_robin() {
+    use std::collections::HashMap;
+
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(4)
+        .dispatch(Dispatch::RoundRobin)
+        .build();
+
+    let runs = Arc::new(Mutex::new(HashMap::new()));
+
+    for i in 0..40 {
+        let runs = Arc::clone(&runs);
+
+        pool.execute(move || {
+            // Uneven jobs would skew the distribution of a shared queue
+            thread::sleep(Duration::from_millis(i % 4 * 5));
+
+            let name = thread::current().name().unwrap().to_owned();
+            *runs.lock().unwrap().entry(name).or_insert(0) += 1;
+        });
+    }
+
+    pool.join();
+
+    let runs = runs.lock().unwrap();
+    assert_eq!(runs.len(), 4);
+    assert!(runs.values().all(|&count| count == 10));
+    drop(runs);
+
+    // The jobs of a removed worker are handed to the remaining ones
+    let (sender, receiver) = mpsc::channel();
+
+    for i in 0..12 {
+        let sender = sender.clone();
+        pool.execute(move || sender.send(i).unwrap());
+    }
+
+    pool.remove_workers(3);
+    drop(sender);
+
+    assert_eq!(receiver.iter().count(), 12);
+}
+
+#[test]
+fn wait_for_idle() {
+    let pool = ThreadPool::new(2);
+    assert!(pool.wait_for_idle(Duration::ZERO));
+
+    pool.execute(|| thread::sleep(Duration::from_millis(300)));
+
+    assert!(!pool.wait_for_idle(Duration::from_millis(50)));
+    assert!(pool.wait_for_idle(Duration::from_secs(5)));
+}
+
+#[test]
+fn execute_with_retry() {
+    use std::sync::atomic::AtomicBool;
+
+    let panics = Arc::new(AtomicUsize::new(0));
+
+    let handled = Arc::clone(&panics);
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(2)
+        .panic_handler(move |_| {
+            handled.fetch_add(1, Ordering::SeqCst);
+        })
+        .build();
+
+    // Succeeds on the third attempt
+    let attempts = Arc::new(AtomicUsize::new(0));
+    let succeeded = Arc::new(AtomicBool::new(false));
+
+    let (counter, success) = (Arc::clone(&attempts), Arc::clone(&succeeded));
+    pool.execute_with_retry(3, move || {
+        if counter.fetch_add(1, Ordering::SeqCst) < 2 {
+            panic!("flaky");
+        }
+
+        success.store(true, Ordering::SeqCst);
+    });
+
+    pool.join();
+    assert_eq!(attempts.load(Ordering::SeqCst), 3);
+    assert!(succeeded.load(Ordering::SeqCst));
+    assert_eq!(panics.load(Ordering::SeqCst), 0);
+
+    // Only the last failure reaches the panic handler
+    let attempts = Arc::new(AtomicUsize::new(0));
+
+    let counter = Arc::clone(&attempts);
+    pool.execute_with_retry(2, move || {
+        counter.fetch_add(1, Ordering::SeqCst);
+        panic!("broken");
+    });
+
+    pool.join();
+    assert_eq!(attempts.load(Ordering::SeqCst), 2);
+    assert_eq!(panics.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn execute_with_retry_full_queue() {
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(1)
+        .queue_capacity(1)
+        .panic_handler(|_| {})
+        .build();
+
+    let attempts = Arc::new(AtomicUsize::new(0));
+    let (started_sender, started) = mpsc::channel();
+    let (release, released) = mpsc::channel::<()>();
+    let released = Mutex::new(released);
+
+    let counter = Arc::clone(&attempts);
+    pool.execute_with_retry(2, move || {
+        if counter.fetch_add(1, Ordering::SeqCst) == 0 {
+            started_sender.send(()).unwrap();
+            released.lock().unwrap().recv().unwrap();
+            panic!("flaky");
+        }
+    });
+
+    // The retry is queued while the only slot is taken
+    started.recv().unwrap();
+    pool.execute(|| {});
+    release.send(()).unwrap();
+
+    pool.join();
+    assert_eq!(attempts.load(Ordering::SeqCst), 2);
+
+    // No worker is waiting while the only one runs the retried job
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(1)
+        .dispatch(Dispatch::Synchronous)
+        .panic_handler(|_| {})
+        .build();
+
+    let attempts = Arc::new(AtomicUsize::new(0));
+
+    let counter = Arc::clone(&attempts);
+    pool.execute_with_retry(3, move || {
+        if counter.fetch_add(1, Ordering::SeqCst) < 2 {
+            panic!("flaky");
+        }
+    });
+
+    pool.join();
+    assert_eq!(attempts.load(Ordering::SeqCst), 3);
+}
+
+#[test]
+fn execute_on() {
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(4)
+        .dispatch(Dispatch::RoundRobin)
+        .build();
+
+    let (sender, receiver) = mpsc::channel();
+
+    for i in 0..20 {
+        let sender = sender.clone();
+
+        pool.execute_on(i % 2, move || {
+            let name = thread::current().name().unwrap().to_owned();
+            sender.send((i % 2, name)).unwrap();
+        });
+    }
+
+    drop(sender);
+
+    for (index, name) in receiver {
+        assert_eq!(name, format!("threatpool-worker-{index}"));
+    }
+
+    let result = panic::catch_unwind(AssertUnwindSafe(|| pool.execute_on(4, || {})));
+    assert!(result.is_err());
+}
+
+#[test]
+fn shutdown_timeout() {
+    let pool = ThreadPool::new(2);
+    pool.execute(|| thread::sleep(Duration::from_millis(500)));
+
+    // Give the job time to be picked up
+    thread::sleep(Duration::from_millis(50));
+
+    let stragglers = pool.shutdown_timeout(Duration::from_millis(50)).unwrap_err();
+    assert_eq!(stragglers.len(), 1);
+
+    for thread in stragglers {
+        thread.join().unwrap();
+    }
+
+    let pool = ThreadPool::new(2);
+    pool.execute(|| thread::sleep(Duration::from_millis(10)));
+    assert!(pool.shutdown_timeout(Duration::from_secs(5)).is_ok());
+}
+
+#[test]
+fn execute_tracked() {
+    let pool = ThreadPool::new(1);
+
+    pool.execute(|| thread::sleep(Duration::from_millis(300)));
+    let (handle, token) = pool.execute_tracked(|| 1);
+
+    // The handle resolves right away instead of after the slow job
+    let start = Instant::now();
+    token.cancel();
+
+    assert!(matches!(handle.join(), Err(JobError::Cancelled)));
+    assert!(start.elapsed() < Duration::from_millis(200));
+
+    let (handle, token) = pool.execute_tracked(|| 2);
+    assert_eq!(handle.join().unwrap(), 2);
+
+    // Cancelling a finished job has no effect
+    token.cancel();
+}
+
+#[test]
+fn schedule_recurring() {
+    let pool = ThreadPool::new(2);
+    let counter = Arc::new(AtomicUsize::new(0));
+
+    let ticks = Arc::clone(&counter);
+    let handle = pool.schedule_recurring(Duration::from_millis(20), move || {
+        ticks.fetch_add(1, Ordering::SeqCst);
+    });
+
+    thread::sleep(Duration::from_millis(110));
+    handle.stop();
+    pool.join();
+
+    let count = counter.load(Ordering::SeqCst);
+    assert!((3..=6).contains(&count), "ran {count} times");
+
+    // No more runs after stopping
+    thread::sleep(Duration::from_millis(60));
+    assert_eq!(counter.load(Ordering::SeqCst), count);
+}
+
+#[test]
+fn event_sink() {
+    let (sender, receiver) = mpsc::channel();
+    let pool = ThreadPoolBuilder::new().num_threads(2).event_sink(sender).build();
+
+    pool.execute_named("work", || thread::sleep(Duration::from_millis(10)));
+    pool.join();
+
+    let events: Vec<_> = receiver.try_iter().collect();
+    let name = Some("work".to_owned());
+
+    assert_eq!(events.len(), 3);
+    assert_eq!(events[0], PoolEvent::JobSubmitted);
+    assert_eq!(events[1], PoolEvent::JobStarted { name: name.clone() });
+
+    match &events[2] {
+        PoolEvent::JobFinished { name: finished, duration } => {
+            assert_eq!(*finished, name);
+            assert!(*duration >= Duration::from_millis(10));
+        }
+
+        event => panic!("unexpected event {event:?}"),
+    }
+
+    pool.execute(|| panic!("boom"));
+    pool.join();
+
+    let events: Vec<_> = receiver.try_iter().collect();
+    assert_eq!(events[2], PoolEvent::JobPanicked { name: None });
+    assert!(matches!(events[3], PoolEvent::JobFinished { name: None, .. }));
+}
+
+#[test]
+fn slow_job_threshold() {
+    let (sender, receiver) = mpsc::channel();
+
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(2)
+        .slow_job_threshold(Duration::from_millis(30))
+        .on_slow_job(move |name, elapsed| {
+            let _ = sender.send((name.map(str::to_owned), elapsed));
+        })
+        .build();
+
+    pool.execute_named("fast", || thread::sleep(Duration::from_millis(1)));
+    pool.execute_named("slow", || thread::sleep(Duration::from_millis(100)));
+    pool.join();
+
+    // The slow job is reported exactly once
+    let reports: Vec<_> = receiver.try_iter().collect();
+    assert_eq!(reports.len(), 1);
+    assert_eq!(reports[0].0.as_deref(), Some("slow"));
+    assert!(reports[0].1 > Duration::from_millis(30));
+}
+
+#[test]
+fn spawn_iter() {
+    use std::collections::HashSet;
+
+    let pool = ThreadPool::new(4);
+
+    let results: Vec<_> = pool
+        .spawn_iter(0..20u64, |i| {
+            // A cheap stand-in for random sleeps
+            thread::sleep(Duration::from_millis(i * 7919 % 13));
+            i * 2
+        })
+        .collect();
+
+    assert_eq!(results.len(), 20);
+
+    let results: HashSet<_> = results.into_iter().collect();
+    assert_eq!(results, (0..20).map(|i| i * 2).collect());
+}
+
+#[test]
+fn spawn_handler() {
+    let spawned = Arc::new(AtomicUsize::new(0));
+
+    let counter = Arc::clone(&spawned);
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(3)
+        .spawn_handler(move |main| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            thread::spawn(main)
+        })
+        .build();
+
+    assert_eq!(spawned.load(Ordering::SeqCst), 3);
+    assert_eq!(pool.execute_with_result(|| 42).join().unwrap(), 42);
+
+    pool.add_workers(1).unwrap();
+    assert_eq!(spawned.load(Ordering::SeqCst), 4);
+}
+
+#[test]
+fn result_panic() {
+    let panics = Arc::new(AtomicUsize::new(0));
+
+    let handled = Arc::clone(&panics);
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(2)
+        .panic_handler(move |_| {
+            handled.fetch_add(1, Ordering::SeqCst);
+        })
+        .build();
+
+    let handle = pool.execute_with_result(|| -> u32 { panic!("boom") });
+
+    match handle.join() {
+        Err(JobError::Panic(payload)) => assert_eq!(payload.downcast_ref(), Some(&"boom")),
+        _ => panic!("the panic wasn't passed to the handle"),
+    }
+
+    // The panic went to the handle instead of the panic handler
+    assert_eq!(panics.load(Ordering::SeqCst), 0);
+    assert_eq!(pool.execute_with_result(|| 1).join().unwrap(), 1);
+}
+
+#[test]
+fn submitted_count() {
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(4)
+        .queue_capacity(1)
+        .build();
+
+    for _ in 0..100 {
+        pool.execute(|| {});
+    }
+
+    pool.join();
+    assert_eq!(pool.submitted_count(), 100);
+    assert_eq!(pool.completed_count(), 100);
+
+    // Rejected jobs aren't counted
+    pool.drain();
+    assert!(pool.try_execute(|| {}).is_err());
+    assert_eq!(pool.submitted_count(), 100);
+}
+
+#[test]
+fn execute_boxed() {
+    let pool = ThreadPool::new(4);
+    let counter = Arc::new(AtomicUsize::new(0));
+
+    let jobs: Vec<Box<dyn FnOnce() + Send>> = (1..=10)
+        .map(|i| {
+            let counter = Arc::clone(&counter);
+            Box::new(move || {
+                counter.fetch_add(i, Ordering::SeqCst);
+            }) as Box<dyn FnOnce() + Send>
+        })
+        .collect();
+
+    for job in jobs {
+        pool.execute_boxed(job);
+    }
+
+    pool.join();
+    assert_eq!(counter.load(Ordering::SeqCst), 55);
+}
+
+#[test]
+fn execute_acked() {
+    use std::sync::Barrier;
+
+    let pool = ThreadPool::new(1);
+    let barrier = Arc::new(Barrier::new(2));
+
+    let blocker = Arc::clone(&barrier);
+    pool.execute(move || {
+        blocker.wait();
+    });
+
+    let ack = pool.execute_acked(|| {});
+
+    // The job is queued, but can't start before the blocking job is done
+    thread::sleep(Duration::from_millis(50));
+    assert!(!ack.has_started());
+
+    barrier.wait();
+    assert!(ack.wait());
+    assert!(ack.has_started());
+}
+
+#[test]
+fn try_new() {
+    assert_eq!(ThreadPool::try_new(0).err(), Some(ThreadPoolError::InvalidSize));
+
+    let pool = ThreadPool::try_new(4).unwrap();
+    assert_eq!(pool.size(), 4);
+
+    assert!(panic::catch_unwind(|| ThreadPool::new(0)).is_err());
+}
+
+#[test]
+fn survives_panicking_jobs() {
+    let counter = Arc::new(AtomicUsize::new(0));
+
+    let pool = ThreadPool::new(4);
+
+    for i in 0..100 {
+        let counter = Arc::clone(&counter);
+
+        pool.execute(move || {
+            if i == 50 {
+                panic!("one bad job");
+            }
+
+            counter.fetch_add(1, Ordering::SeqCst);
+        });
+    }
+
+    pool.join();
+    assert_eq!(counter.load(Ordering::SeqCst), 99);
+
+    // Dropping the last handle from within a job must not join that job's own thread
+    let (sender, receiver) = mpsc::channel();
+    let handle = pool.clone();
+
+    pool.execute(move || {
+        thread::sleep(Duration::from_millis(50));
+        drop(handle);
+        sender.send(()).unwrap();
+    });
+
+    drop(pool);
+    receiver.recv_timeout(Duration::from_secs(5)).unwrap();
+}
+
+#[test]
+fn rejection_policy() {
+    use std::sync::Barrier;
+
+    let build = |policy| {
+        ThreadPoolBuilder::new()
+            .num_threads(1)
+            .queue_capacity(1)
+            .rejection_policy(policy)
+            .build()
+    };
+
+    // Occupies the worker until the barrier is released, and fills the queue
+    let block = |pool: &ThreadPool, log: &Arc<Mutex<Vec<&'static str>>>| {
+        let barrier = Arc::new(Barrier::new(2));
+        let blocker = Arc::clone(&barrier);
+
+        pool.execute(move || {
+            blocker.wait();
+        });
+
+        while pool.active_count() == 0 {
+            thread::yield_now();
         }
 
-        for worker in &mut self.workers {
-            if let Some(thread) = worker.thread.take() {
-                thread.join().unwrap();
+        let log = Arc::clone(log);
+        pool.execute(move || log.lock().unwrap().push("queued"));
+
+        barrier
+    };
+
+    let log = Arc::new(Mutex::new(Vec::new()));
+
+    let pool = build(RejectionPolicy::CallerRuns);
+    let barrier = block(&pool, &log);
+    let caller = thread::current().id();
+
+    let (sender, receiver) = mpsc::channel();
+    pool.execute(move || sender.send(thread::current().id()).unwrap());
+    assert_eq!(receiver.recv().unwrap(), caller);
+
+    barrier.wait();
+    pool.join();
+
+    let pool = build(RejectionPolicy::Abort);
+    let barrier = block(&pool, &log);
+    let err = pool.try_execute(|| {}).unwrap_err();
+    assert_eq!(err.error(), ThreadPoolError::QueueFull);
+
+    // A scoped job is rejected the same way
+    let result = panic::catch_unwind(AssertUnwindSafe(|| pool.scope(|scope| scope.spawn(|| {}))));
+    let message = result.unwrap_err().downcast::<String>().unwrap();
+    assert_eq!(*message, format!("failed to spawn a scoped job: {}", ThreadPoolError::QueueFull));
+
+    barrier.wait();
+    pool.join();
+
+    // The queued job is dropped in favor of the new one
+    log.lock().unwrap().clear();
+
+    let pool = build(RejectionPolicy::DiscardOldest);
+    let barrier = block(&pool, &log);
+
+    let newer = Arc::clone(&log);
+    pool.execute(move || newer.lock().unwrap().push("newer"));
+
+    barrier.wait();
+    pool.join();
+    assert_eq!(*log.lock().unwrap(), ["newer"]);
+    assert_eq!(pool.completed_count(), 2);
+}
+
+#[test]
+fn with_context() {
+    struct Context {
+        counter: AtomicUsize,
+    }
+
+    let pool = ThreadPool::with_context(4, Context { counter: AtomicUsize::new(0) });
+
+    for _ in 0..16 {
+        pool.execute_ctx(|context| {
+            context.counter.fetch_add(1, Ordering::SeqCst);
+        });
+    }
+
+    pool.pool().join();
+    assert_eq!(pool.context().counter.load(Ordering::SeqCst), 16);
+}
+
+#[test]
+fn synchronous_dispatch() {
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(1)
+        .dispatch(Dispatch::Synchronous)
+        .build();
+
+    let (release, wait) = mpsc::channel::<()>();
+    let wait = Arc::new(Mutex::new(wait));
+    let submitted = Arc::new(AtomicUsize::new(0));
+
+    let producer = {
+        let pool = pool.clone();
+        let wait = Arc::clone(&wait);
+        let submitted = Arc::clone(&submitted);
+
+        thread::spawn(move || {
+            for _ in 0..3 {
+                let wait = Arc::clone(&wait);
+
+                pool.execute(move || wait.lock().unwrap().recv().unwrap());
+                submitted.fetch_add(1, Ordering::SeqCst);
             }
+        })
+    };
+
+    // Each job is only taken once the worker has finished the previous one
+    for expected in 1..=3 {
+        thread::sleep(Duration::from_millis(50));
+        assert_eq!(submitted.load(Ordering::SeqCst), expected);
+        assert_eq!(pool.active_count(), 1);
+
+        release.send(()).unwrap();
+    }
+
+    producer.join().unwrap();
+    pool.join();
+
+    // Draining unblocks a parked producer
+    let (release, wait) = mpsc::channel::<()>();
+    pool.execute(move || wait.recv().unwrap());
+
+    let producer = {
+        let pool = pool.clone();
+        thread::spawn(move || pool.try_execute(|| {}).is_err())
+    };
+
+    thread::sleep(Duration::from_millis(50));
+
+    let drain = {
+        let pool = pool.clone();
+        thread::spawn(move || pool.drain())
+    };
+
+    assert!(producer.join().unwrap());
+
+    release.send(()).unwrap();
+    drain.join().unwrap();
+}
+
+#[test]
+fn timing_callback() {
+    let durations = Arc::new(Mutex::new(Vec::new()));
+    let recorder = Arc::clone(&durations);
+
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(2)
+        .timing_callback(move |duration| recorder.lock().unwrap().push(duration))
+        .build();
+
+    for _ in 0..4 {
+        pool.execute(|| thread::sleep(Duration::from_millis(20)));
+    }
+
+    pool.join();
+
+    let durations = durations.lock().unwrap();
+    assert_eq!(durations.len(), 4);
+    assert!(durations.iter().all(|duration| *duration >= Duration::from_millis(20)));
+}
+
+#[test]
+fn join_and_help() {
+    let pool = ThreadPool::new(1);
+    let threads = Arc::new(Mutex::new(Vec::new()));
+
+    for _ in 0..50 {
+        let threads = Arc::clone(&threads);
+
+        pool.execute(move || {
+            thread::sleep(Duration::from_millis(1));
+            threads.lock().unwrap().push(thread::current().id());
+        });
+    }
+
+    pool.join_and_help();
+
+    let threads = threads.lock().unwrap();
+    assert_eq!(threads.len(), 50);
+    assert!(threads.contains(&thread::current().id()));
+    assert_eq!(pool.completed_count(), 50);
+}
+
+#[test]
+fn equal_priority_in_submission_order() {
+    let pool = ThreadPool::builder().num_threads(1).build();
+    let log = Arc::new(Mutex::new(Vec::new()));
+
+    let (release, wait) = mpsc::channel::<()>();
+
+    // Queue every job before the worker pops any of them
+    pool.execute(move || wait.recv().unwrap());
+
+    for i in 0..1000 {
+        let log = Arc::clone(&log);
+        pool.execute_with_priority(3, move || log.lock().unwrap().push(i));
+    }
+
+    release.send(()).unwrap();
+    pool.join();
+
+    assert_eq!(*log.lock().unwrap(), (0..1000).collect::<Vec<_>>());
+}
+
+#[test]
+fn lifo_order() {
+    let pool = ThreadPool::builder().num_threads(1).order(QueueOrder::Lifo).build();
+    let log = Arc::new(Mutex::new(Vec::new()));
+
+    // Make sure the worker is busy before queueing, or it would pop the newest job first
+    let release = block_worker(&pool);
+
+    for name in ["A", "B", "C"] {
+        let log = Arc::clone(&log);
+        pool.execute(move || log.lock().unwrap().push(name));
+    }
+
+    release.send(()).unwrap();
+    pool.join();
+
+    assert_eq!(*log.lock().unwrap(), ["C", "B", "A"]);
+}
+
+#[test]
+fn shutdown_collecting() {
+    use std::sync::Barrier;
+
+    for (order, expected) in [(QueueOrder::Fifo, [0, 1, 2]), (QueueOrder::Lifo, [2, 1, 0])] {
+        let pool = ThreadPool::builder().num_threads(1).order(order).build();
+        let barrier = Arc::new(Barrier::new(2));
+        let log = Arc::new(Mutex::new(Vec::new()));
+
+        let blocker = Arc::clone(&barrier);
+        pool.execute(move || {
+            blocker.wait();
+            blocker.wait();
+        });
+        barrier.wait();
+
+        for i in 0..3 {
+            let log = Arc::clone(&log);
+            pool.execute(move || log.lock().unwrap().push(i));
         }
+
+        let collector = {
+            let pool = pool.clone();
+            thread::spawn(move || pool.shutdown_collecting())
+        };
+
+        // Release the worker only once the queued jobs have been collected
+        while pool.queued_count() > 0 {
+            thread::yield_now();
+        }
+
+        barrier.wait();
+        let jobs = collector.join().unwrap();
+
+        assert!(log.lock().unwrap().is_empty());
+        assert_eq!(jobs.len(), 3);
+        assert!(pool.is_idle());
+
+        for job in jobs {
+            job();
+        }
+
+        assert_eq!(*log.lock().unwrap(), expected);
     }
 }
 
 #[test]
-fn main() {
-    let pool = ThreadPool::new(10);
+fn remaining_capacity() {
+    let pool = ThreadPool::builder().num_threads(1).queue_capacity(4).build();
+    let release = block_worker(&pool);
 
-    for _ in 0..10000000 {
-        pool.execute(|| {
-            println!("Hello, World!"); // Simulates some heavy task
+    pool.execute(|| {});
+    pool.execute(|| {});
+    assert_eq!(pool.remaining_capacity(), Some(2));
+
+    release.send(()).unwrap();
+    pool.join();
+    assert_eq!(pool.remaining_capacity(), Some(4));
+
+    // Idle workers wait for a job one at a time
+    let pool = ThreadPool::builder().num_threads(4).dispatch(Dispatch::Synchronous).build();
+
+    while pool.idle_count() < 4 || pool.remaining_capacity() != Some(1) {
+        thread::yield_now();
+    }
+
+    thread::sleep(Duration::from_millis(10));
+    assert_eq!(pool.remaining_capacity(), Some(1));
+}
+
+#[test]
+fn pool_registry() {
+    let registry = PoolRegistry::new(2);
+    let pools = [ThreadPool::new_shared(2, &registry), ThreadPool::new_shared(2, &registry)];
+
+    let running = Arc::new(AtomicUsize::new(0));
+    let max_running = Arc::new(AtomicUsize::new(0));
+    let threads = Arc::new(Mutex::new(std::collections::HashSet::new()));
+
+    for pool in &pools {
+        for _ in 0..20 {
+            let running = Arc::clone(&running);
+            let max_running = Arc::clone(&max_running);
+            let threads = Arc::clone(&threads);
+
+            pool.execute(move || {
+                let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                max_running.fetch_max(now, Ordering::SeqCst);
+                threads.lock().unwrap().insert(thread::current().id());
+
+                thread::sleep(Duration::from_millis(1));
+                running.fetch_sub(1, Ordering::SeqCst);
+            });
+        }
+    }
+
+    for pool in &pools {
+        pool.join();
+        assert_eq!(pool.pending(), 0);
+    }
+
+    assert!(max_running.load(Ordering::SeqCst) <= 2);
+    assert!(threads.lock().unwrap().len() <= 2);
+    assert_eq!(registry.num_threads(), 2);
+}
+
+#[test]
+fn par_chunks_mut() {
+    let pool = ThreadPool::new(4);
+    let mut numbers: Vec<usize> = (0..1000).collect();
+
+    pool.par_chunks_mut(&mut numbers, 100, |chunk| {
+        assert_eq!(chunk.len(), 100);
+
+        for number in chunk {
+            *number *= 2;
+        }
+    });
+
+    assert_eq!(numbers, (0..1000).map(|i| i * 2).collect::<Vec<_>>());
+}
+
+#[test]
+fn job_timing() {
+    let pool = ThreadPool::new(1);
+
+    pool.execute(|| thread::sleep(Duration::from_millis(50)));
+    let handle = pool.execute_with_result(|| thread::sleep(Duration::from_millis(10)));
+
+    assert_eq!(handle.timing(), None);
+    pool.join();
+
+    let timing = handle.timing().unwrap();
+    assert!(timing.queue_time() >= Duration::from_millis(30));
+    assert!(timing.run_time() >= Duration::from_millis(10));
+    assert!(timing.submitted <= timing.started && timing.started <= timing.finished);
+
+    assert!(handle.join().is_ok());
+}
+
+#[test]
+fn executor() {
+    fn spawn_send(executor: &dyn Executor, sender: mpsc::Sender<thread::ThreadId>) {
+        executor.spawn(Box::new(move || sender.send(thread::current().id()).unwrap()));
+    }
+
+    struct Inline;
+
+    impl Executor for Inline {
+        fn spawn(&self, job: Box<dyn FnOnce() + Send + 'static>) {
+            job();
+        }
+    }
+
+    let (sender, receiver) = mpsc::channel();
+
+    let pool = ThreadPool::new(2);
+    spawn_send(&pool, sender.clone());
+    assert_ne!(receiver.recv().unwrap(), thread::current().id());
+
+    pool.join();
+    assert_eq!(pool.completed_count(), 1);
+
+    spawn_send(&Inline, sender);
+    assert_eq!(receiver.recv().unwrap(), thread::current().id());
+}
+
+#[test]
+fn shutdown_with_uneven_jobs() {
+    let pool = ThreadPool::new(4);
+    let done = Arc::new(AtomicUsize::new(0));
+
+    for i in 0..16 {
+        let done = Arc::clone(&done);
+
+        pool.execute(move || {
+            // The first worker to pick up a job is stuck on it the longest
+            let millis = if i == 0 { 200 } else { i % 3 * 5 };
+            thread::sleep(Duration::from_millis(millis));
+            done.fetch_add(1, Ordering::SeqCst);
+        });
+    }
+
+    let start = Instant::now();
+    pool.shutdown();
+
+    assert_eq!(done.load(Ordering::SeqCst), 16);
+    assert!(start.elapsed() < Duration::from_secs(5));
+}
+
+#[test]
+fn try_join() {
+    let pool = ThreadPool::new(2);
+    let (release, wait) = mpsc::channel::<()>();
+
+    pool.execute(move || wait.recv().unwrap());
+    assert!(!pool.try_join());
+
+    release.send(()).unwrap();
+    assert!(pool.wait_for_idle(Duration::from_secs(5)));
+    assert!(pool.try_join());
+}
+
+#[test]
+fn job_queue() {
+    #[derive(Default)]
+    struct Stack(Vec<QueuedJob>);
+
+    impl JobQueue for Stack {
+        fn push(&mut self, job: QueuedJob) {
+            self.0.push(job);
+        }
+
+        fn pop(&mut self) -> Option<QueuedJob> {
+            self.0.pop()
+        }
+    }
+
+    let pool = ThreadPool::builder().num_threads(1).job_queue(Stack::default).build();
+    let log = Arc::new(Mutex::new(Vec::new()));
+
+    // Hold all three jobs in the custom queue until they can be popped together
+    let release = block_worker(&pool);
+
+    for name in ["A", "B", "C"] {
+        let log = Arc::clone(&log);
+        pool.execute(move || log.lock().unwrap().push(name));
+    }
+
+    assert_eq!(pool.queued_count(), 3);
+
+    release.send(()).unwrap();
+    pool.join();
+
+    assert_eq!(*log.lock().unwrap(), ["C", "B", "A"]);
+}
+
+#[test]
+fn spawn_failed() {
+    assert_eq!(ThreadPoolError::SpawnFailed.to_string(), "failed to spawn a worker thread");
+
+    let pool = ThreadPool::try_new(4).ok().unwrap();
+    assert_eq!(pool.size(), 4);
+    assert_eq!(pool.health().len(), 4);
+
+    // The stack of the first worker can't be allocated
+    let result = ThreadPoolBuilder::new().num_threads(2).stack_size(usize::MAX).try_build();
+    assert_eq!(result.err(), Some(ThreadPoolError::SpawnFailed));
+}
+
+#[test]
+fn failed_count() {
+    let pool = ThreadPool::new(4);
+
+    // 3 of the 10 jobs panic
+    for i in 0..10 {
+        pool.execute(move || assert!(i >= 3, "job {i} failed"));
+    }
+
+    pool.join();
+
+    assert_eq!(pool.failed_count(), 3);
+    assert_eq!(pool.completed_count(), 10);
+    assert_eq!(pool.stats().failed, 3);
+}
+
+#[test]
+fn pause_and_resume() {
+    let pool = ThreadPool::new(2);
+    let done = Arc::new(AtomicUsize::new(0));
+
+    pool.pause();
+    assert!(pool.is_paused());
+
+    for _ in 0..8 {
+        let done = Arc::clone(&done);
+        pool.execute(move || {
+            done.fetch_add(1, Ordering::SeqCst);
+        });
+    }
+
+    thread::sleep(Duration::from_millis(50));
+    assert_eq!(done.load(Ordering::SeqCst), 0);
+    assert_eq!(pool.queued_count(), 8);
+
+    pool.resume();
+    assert!(!pool.is_paused());
+    pool.join();
+    assert_eq!(done.load(Ordering::SeqCst), 8);
+
+    // Shutting down runs the jobs queued while paused
+    pool.pause();
+
+    let counter = Arc::clone(&done);
+    pool.execute(move || {
+        counter.fetch_add(1, Ordering::SeqCst);
+    });
+
+    pool.shutdown();
+    assert_eq!(done.load(Ordering::SeqCst), 9);
+}
+
+#[test]
+fn join_set() {
+    let pool = ThreadPool::new(4);
+    let mut set = JoinSet::new(&pool);
+
+    for i in 0..10 {
+        set.spawn(move || {
+            assert!(i != 7, "job 7 failed");
+            i
         });
     }
+
+    assert_eq!(set.len(), 10);
+
+    let mut results = Vec::new();
+    let mut panics = 0;
+
+    while let Some(result) = set.join_next() {
+        match result {
+            Ok(i) => results.push(i),
+            Err(JobError::Panic(_)) => panics += 1,
+            Err(err) => panic!("{err}"),
+        }
+    }
+
+    results.sort();
+    assert_eq!(results, [0, 1, 2, 3, 4, 5, 6, 8, 9]);
+    assert_eq!(panics, 1);
+    assert!(set.is_empty());
+
+    // Jobs dropped without running resolve instead of hanging
+    let pool = ThreadPool::new(1);
+    let (release, wait) = mpsc::channel::<()>();
+    pool.execute(move || wait.recv().unwrap());
+
+    let set = pool.spawn_many((0..3).map(|i| move || i));
+    let collected = {
+        let pool = pool.clone();
+        thread::spawn(move || pool.shutdown_collecting())
+    };
+
+    while pool.queued_count() > 0 {
+        thread::yield_now();
+    }
+
+    release.send(()).unwrap();
+    drop(collected.join().unwrap());
+
+    let results = set.join_all();
+    assert_eq!(results.len(), 3);
+    assert!(results.iter().all(|result| matches!(result, Err(JobError::Disconnected))));
+}
+
+#[test]
+fn drop_behavior() {
+    let pool = ThreadPool::builder().num_threads(1).drop_behavior(DropBehavior::Detach).build();
+    let (sender, receiver) = mpsc::channel();
+
+    pool.execute(move || {
+        thread::sleep(Duration::from_millis(200));
+        sender.send(()).unwrap();
+    });
+
+    let start = Instant::now();
+    drop(pool);
+    assert!(start.elapsed() < Duration::from_millis(100));
+
+    // The job still finishes in the background
+    receiver.recv().unwrap();
+}
+
+#[test]
+fn execute_sized() {
+    let pool = ThreadPool::new(1);
+    let release = block_worker(&pool);
+
+    let small = [1u8; 16];
+    let large = [1u64; 128];
+
+    pool.execute_sized(move || assert_eq!(small.len(), 16));
+    pool.execute_sized(move || assert_eq!(large.len(), 128));
+    pool.execute(|| {});
+
+    assert_eq!(pool.queued_bytes(), 16 + 1024);
+
+    release.send(()).unwrap();
+    pool.join();
+
+    assert_eq!(pool.queued_bytes(), 0);
+}
+
+#[test]
+fn execute_if_capacity() {
+    use std::sync::Barrier;
+
+    let pool = ThreadPool::builder().num_threads(1).queue_capacity(1).build();
+    let release = block_worker(&pool);
+
+    let accepted = Arc::new(AtomicUsize::new(0));
+    let barrier = Arc::new(Barrier::new(8));
+
+    let threads: Vec<_> = (0..8)
+        .map(|_| {
+            let pool = pool.clone();
+            let accepted = Arc::clone(&accepted);
+            let barrier = Arc::clone(&barrier);
+
+            thread::spawn(move || {
+                barrier.wait();
+
+                for _ in 0..100 {
+                    if pool.execute_if_capacity(|| {}).is_ok() {
+                        accepted.fetch_add(1, Ordering::SeqCst);
+                    }
+                }
+            })
+        })
+        .collect();
+
+    for thread in threads {
+        thread.join().unwrap();
+    }
+
+    // The worker is blocked, so only the single slot could be taken
+    assert_eq!(accepted.load(Ordering::SeqCst), 1);
+    assert_eq!(pool.queued_count(), 1);
+
+    release.send(()).unwrap();
+    pool.join();
 }
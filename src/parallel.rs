@@ -0,0 +1,343 @@
+use std::sync::{mpsc, Arc};
+
+use crate::{Job, ThreadPool};
+
+impl ThreadPool {
+    /// Applies `f` to every item in parallel and returns the results in the
+    /// order of the items
+    ///
+    /// Blocks until every result has arrived.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `f` panics for any of the items.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use threatpool::ThreadPool;
+    ///
+    /// let pool = ThreadPool::new(4);
+    ///
+    /// let squares = pool.map(0..5, |i| i * i);
+    /// assert_eq!(squares, [0, 1, 4, 9, 16]);
+    /// ```
+    pub fn map<I, T, R, F>(&self, items: I, f: F) -> Vec<R>
+    where
+        I: IntoIterator<Item = T>,
+        T: Send + 'static,
+        R: Send + 'static,
+        F: Fn(T) -> R + Send + Sync + 'static,
+    {
+        let f = Arc::new(f);
+        let (sender, receiver) = mpsc::channel();
+
+        let mut len = 0;
+
+        for (index, item) in items.into_iter().enumerate() {
+            let f = Arc::clone(&f);
+            let sender = sender.clone();
+
+            self.execute(move || {
+                let _ = sender.send((index, f(item)));
+            });
+
+            len += 1;
+        }
+
+        // Only the jobs hold senders now, so `recv` fails if one of them panicked
+        drop(sender);
+
+        let mut results: Vec<Option<R>> = (0..len).map(|_| None).collect();
+
+        for _ in 0..len {
+            let (index, result) = receiver.recv().expect("a mapped job panicked");
+            results[index] = Some(result);
+        }
+
+        results.into_iter().map(Option::unwrap).collect()
+    }
+
+    /// Applies `f` to every item in parallel and yields the results in the
+    /// order the jobs finish
+    ///
+    /// Every item is submitted before this returns, the iterator then blocks
+    /// until the next result arrives. It ends once every job has finished, a
+    /// job which panicked doesn't yield a result.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the pool is no longer accepting jobs.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use threatpool::ThreadPool;
+    ///
+    /// let pool = ThreadPool::new(4);
+    ///
+    /// let mut squares: Vec<_> = pool.spawn_iter(0..5, |i| i * i).collect();
+    /// squares.sort();
+    /// assert_eq!(squares, [0, 1, 4, 9, 16]);
+    /// ```
+    pub fn spawn_iter<I, T, R, F>(&self, items: I, f: F) -> impl Iterator<Item = R>
+    where
+        I: IntoIterator<Item = T>,
+        T: Send + 'static,
+        R: Send + 'static,
+        F: Fn(T) -> R + Send + Sync + 'static,
+    {
+        let f = Arc::new(f);
+        let (sender, receiver) = mpsc::channel();
+
+        for item in items {
+            let f = Arc::clone(&f);
+            let sender = sender.clone();
+
+            self.execute(move || {
+                let _ = sender.send(f(item));
+            });
+        }
+
+        // The iterator ends once every job has dropped its sender
+        receiver.into_iter()
+    }
+
+    /// Applies `f` to every item in parallel and blocks until all of them
+    /// have been processed
+    ///
+    /// # Panics
+    ///
+    /// Panics if `f` panics for any of the items.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::sync::atomic::{AtomicUsize, Ordering};
+    /// use std::sync::Arc;
+    /// use threatpool::ThreadPool;
+    ///
+    /// let pool = ThreadPool::new(4);
+    /// let sum = Arc::new(AtomicUsize::new(0));
+    ///
+    /// let counter = Arc::clone(&sum);
+    /// pool.for_each(1..=4, move |i| {
+    ///     counter.fetch_add(i, Ordering::SeqCst);
+    /// });
+    ///
+    /// assert_eq!(sum.load(Ordering::SeqCst), 10);
+    /// ```
+    pub fn for_each<I, T, F>(&self, items: I, f: F)
+    where
+        I: IntoIterator<Item = T>,
+        T: Send + 'static,
+        F: Fn(T) + Send + Sync + 'static,
+    {
+        let f = Arc::new(f);
+        let (sender, receiver) = mpsc::channel();
+
+        let mut len = 0;
+
+        for item in items {
+            let f = Arc::clone(&f);
+            let sender = sender.clone();
+
+            self.execute(move || {
+                f(item);
+                let _ = sender.send(());
+            });
+
+            len += 1;
+        }
+
+        // Only the jobs hold senders now, so `recv` fails if one of them panicked
+        drop(sender);
+
+        for _ in 0..len {
+            receiver.recv().expect("a for_each job panicked");
+        }
+    }
+
+    /// Applies `f` to every `chunk_size` long chunk of `data` in parallel and
+    /// blocks until all of them have been processed
+    ///
+    /// The chunks don't overlap, so every job gets mutable access to its own
+    /// chunk. The last chunk is shorter if the length of `data` isn't a
+    /// multiple of `chunk_size`. The jobs are run in a [`ThreadPool::scope`],
+    /// so the same caveats about calling this from within a job apply.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `chunk_size` is zero or if `f` panics for any of the chunks.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use threatpool::ThreadPool;
+    ///
+    /// let pool = ThreadPool::new(4);
+    /// let mut numbers = vec![1, 2, 3, 4, 5];
+    ///
+    /// pool.par_chunks_mut(&mut numbers, 2, |chunk| {
+    ///     for number in chunk {
+    ///         *number *= 10;
+    ///     }
+    /// });
+    ///
+    /// assert_eq!(numbers, [10, 20, 30, 40, 50]);
+    /// ```
+    pub fn par_chunks_mut<T, F>(&self, data: &mut [T], chunk_size: usize, f: F)
+    where
+        T: Send,
+        F: Fn(&mut [T]) + Send + Sync,
+    {
+        assert!(chunk_size > 0, "the chunk size must be greater than zero");
+
+        let f = &f;
+
+        self.scope(|scope| {
+            for chunk in data.chunks_mut(chunk_size) {
+                scope.spawn(move || f(chunk));
+            }
+        });
+    }
+
+    /// Combines all items into a single value with `f`, starting from `identity`
+    ///
+    /// The items are split into one chunk per worker, which are folded in
+    /// parallel before the partial results are combined on the calling thread.
+    /// `identity` is used once per chunk, so combining it with any value must
+    /// yield that value. Chunks are combined in order, so `f` only needs to be
+    /// associative. Returns `identity` if there are no items.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `f` panics.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use threatpool::ThreadPool;
+    ///
+    /// let pool = ThreadPool::new(4);
+    ///
+    /// let sum = pool.reduce(1..=100, 0, |a, b| a + b);
+    /// assert_eq!(sum, 5050);
+    /// ```
+    pub fn reduce<I, T, F>(&self, items: I, identity: T, f: F) -> T
+    where
+        I: IntoIterator<Item = T>,
+        T: Send + Clone + 'static,
+        F: Fn(T, T) -> T + Send + Sync + 'static,
+    {
+        let items: Vec<T> = items.into_iter().collect();
+
+        if items.is_empty() {
+            return identity;
+        }
+
+        let workers = self.inner.shared.queue.workers().max(1);
+        let chunk_size = items.len().div_ceil(workers);
+
+        let mut items = items.into_iter();
+        let mut chunks = Vec::new();
+
+        loop {
+            let chunk: Vec<T> = items.by_ref().take(chunk_size).collect();
+
+            if chunk.is_empty() {
+                break;
+            }
+
+            chunks.push((identity.clone(), chunk));
+        }
+
+        let f = Arc::new(f);
+        let fold = Arc::clone(&f);
+
+        let partials = self.map(chunks, move |(start, chunk)| {
+            chunk.into_iter().fold(start, |acc, item| fold(acc, item))
+        });
+
+        partials.into_iter().fold(identity, |acc, partial| f(acc, partial))
+    }
+
+    /// Executes `f` and passes its result to `then`, which is executed on `next`
+    ///
+    /// This chains pools into the stages of a pipeline without wiring up
+    /// channels by hand. If `next` no longer accepts jobs once `f` has
+    /// finished, `then` is dropped along with the result.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this pool is no longer accepting jobs.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::sync::mpsc;
+    /// use threatpool::ThreadPool;
+    ///
+    /// let parse = ThreadPool::new(2);
+    /// let store = ThreadPool::new(1);
+    /// let (sender, receiver) = mpsc::channel();
+    ///
+    /// parse.pipe_to(&store, || "42".parse::<u32>().unwrap(), move |number| {
+    ///     sender.send(number).unwrap();
+    /// });
+    ///
+    /// assert_eq!(receiver.recv().unwrap(), 42);
+    /// ```
+    pub fn pipe_to<F, T, G>(&self, next: &ThreadPool, f: F, then: G)
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+        G: FnOnce(T) + Send + 'static,
+    {
+        let next = next.clone();
+
+        self.execute(move || {
+            let result = f();
+            let _ = next.try_execute(move || then(result));
+        });
+    }
+
+    /// Executes `f` once on every worker
+    ///
+    /// Every worker gets a copy of the job pinned to it, which it runs before
+    /// any other queued job. No other thread can take the copy, and a worker
+    /// doesn't retire or expire before it has run its copy. Workers added
+    /// later don't run the job. This returns once the copies are queued, use
+    /// [`ThreadPool::join`] to wait for them to finish.
+    ///
+    /// The copies are queued even if a bounded queue is full, so they can't
+    /// miss a worker which exits in the meantime.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the pool is no longer accepting jobs.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use threatpool::ThreadPool;
+    ///
+    /// let pool = ThreadPool::new(4);
+    ///
+    /// pool.broadcast(|| {
+    ///     println!("Hello from {:?}", std::thread::current().name());
+    /// });
+    /// ```
+    pub fn broadcast<F>(&self, f: F)
+    where
+        F: Fn() + Send + Sync + Clone + 'static,
+    {
+        let result = self.inner.shared.push_broadcast(|workers| {
+            (0..workers).map(|_| Job::new(f.clone())).collect()
+        });
+
+        if result.is_err() {
+            panic!("the thread pool is no longer accepting jobs");
+        }
+    }
+}
@@ -0,0 +1,58 @@
+/// The OS scheduling priority of the worker threads,
+/// see [`ThreadPoolBuilder::thread_priority`](crate::ThreadPoolBuilder::thread_priority)
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Priority {
+    /// Below the default, for background work which shouldn't slow down anything else
+    Low,
+
+    /// The default priority
+    #[default]
+    Normal,
+
+    /// Above the default, which usually requires elevated privileges
+    High,
+}
+
+/// Sets the priority of the current thread
+///
+/// Failing to set it, e.g. because of missing privileges, is ignored.
+#[cfg(target_os = "linux")]
+pub(crate) fn set_current_thread_priority(priority: Priority) {
+    let nice = match priority {
+        Priority::Low => 10,
+        Priority::Normal => 0,
+        Priority::High => -10,
+    };
+
+    // SAFETY: Both calls only take plain integers. On Linux the nice value
+    // applies to the single thread identified by its thread id.
+    unsafe {
+        let tid = libc::syscall(libc::SYS_gettid) as libc::id_t;
+        libc::setpriority(libc::PRIO_PROCESS, tid, nice);
+    }
+}
+
+#[cfg(windows)]
+pub(crate) fn set_current_thread_priority(priority: Priority) {
+    use std::ffi::c_void;
+
+    extern "system" {
+        fn GetCurrentThread() -> *mut c_void;
+        fn SetThreadPriority(thread: *mut c_void, priority: i32) -> i32;
+    }
+
+    let priority = match priority {
+        Priority::Low => -1,
+        Priority::Normal => 0,
+        Priority::High => 1,
+    };
+
+    // SAFETY: The pseudo handle of the current thread is always valid
+    unsafe {
+        SetThreadPriority(GetCurrentThread(), priority);
+    }
+}
+
+/// Setting the priority isn't supported on this platform, so this does nothing
+#[cfg(not(any(target_os = "linux", windows)))]
+pub(crate) fn set_current_thread_priority(_priority: Priority) {}
@@ -0,0 +1,890 @@
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Condvar, Mutex, MutexGuard, PoisonError, RwLock, RwLockReadGuard};
+use std::time::Duration;
+
+use crate::{Dispatch, Job, JobQueue, QueueOrder, QueuedJob, Scheduler};
+
+/// The job queue shared by the pool and its workers
+///
+/// Jobs with a higher priority are popped first, jobs with the same priority
+/// are popped in the order they were pushed, or in reverse with
+/// [`QueueOrder::Lifo`]. With [`Scheduler::WorkStealing`] every worker
+/// additionally owns a local queue with a lock of its own, which it pushes onto
+/// and pops from without locking the shared state. With
+/// [`Dispatch::RoundRobin`] every job goes to one of the assigned queues in
+/// turn, and workers only pop from their own. With [`Dispatch::Synchronous`]
+/// the queue only takes a job while a worker is waiting for one, which only
+/// one worker at a time does. A custom [`JobQueue`] replaces the shared queue,
+/// choosing the order itself.
+/// Broadcast jobs are pinned to one worker each, which pops them first.
+pub(crate) struct Queue {
+    state: Mutex<State>,
+
+    /// The local queues of the workers by their index, only used by
+    /// [`Scheduler::WorkStealing`]
+    ///
+    /// Only `register` and `unregister` write to the map, every other access
+    /// merely locks the local queue it needs.
+    locals: RwLock<BTreeMap<usize, Mutex<VecDeque<Job>>>>,
+
+    /// Whether workers get local queues, which is only the case with
+    /// [`Scheduler::WorkStealing`] on an unbounded, shared queue
+    stealing: bool,
+
+    dispatch: Dispatch,
+
+    /// Whether the queue has been closed, which rejects new jobs
+    ///
+    /// This and the other flags are only written while holding the lock of
+    /// `state`, but read without it by the local queues.
+    closed: AtomicBool,
+
+    /// Whether new jobs are rejected while the queued ones are still popped
+    draining: AtomicBool,
+
+    /// Whether workers wait instead of popping jobs, until the queue is resumed or closed
+    paused: AtomicBool,
+
+    /// The number of threads waiting on `available`
+    waiting: AtomicUsize,
+
+    /// Held while popping, so only one popping thread at a time competes
+    /// with the pushing threads for `state`
+    popping: Mutex<()>,
+
+    /// Notified whenever a job is pushed or the queue is closed
+    available: Condvar,
+
+    /// Notified whenever a job is popped or a worker starts waiting with
+    /// [`Dispatch::Synchronous`], so blocked pushes can retry
+    space: Condvar,
+}
+
+struct State {
+    /// The queued jobs, one FIFO queue per priority
+    jobs: BTreeMap<u8, VecDeque<Job>>,
+
+    /// Holds the jobs of the shared queue instead of `jobs` if set
+    custom: Option<Box<dyn JobQueue>>,
+
+    /// The queues of the workers by their index, only used by [`Dispatch::RoundRobin`]
+    assigned_jobs: BTreeMap<usize, VecDeque<Job>>,
+
+    /// The broadcast jobs by the index of the worker which has to run them
+    ///
+    /// Every worker which hasn't exited yet has an entry, and doesn't exit
+    /// until it has popped its broadcast jobs.
+    broadcasts: BTreeMap<usize, VecDeque<Job>>,
+
+    order: QueueOrder,
+
+    dispatch: Dispatch,
+
+    /// The number of jobs assigned by [`Dispatch::RoundRobin`] so far
+    assigned: usize,
+
+    /// The number of jobs queued in the state, not counting the local queues
+    len: usize,
+
+    /// The maximum number of queued jobs, unbounded if `None`
+    capacity: Option<usize>,
+
+    /// The number of popping threads which should get `Exit::Retired` to make them exit
+    retiring: usize,
+
+    /// The number of workers which haven't exited yet
+    workers: usize,
+
+    /// How long a worker waits for a job before it exits, never if `None`
+    keep_alive: Option<Duration>,
+
+    /// The number of workers which never exit because of `keep_alive`
+    min_workers: usize,
+
+    /// The number of waiting threads which have been notified but didn't wake up yet
+    notified: usize,
+}
+
+/// Where a job is pushed to
+#[derive(Clone, Copy)]
+pub(crate) enum Target {
+    /// The shared queue with the given priority
+    Global(u8),
+
+    /// The local queue of the worker with the given index, only used by
+    /// [`Scheduler::WorkStealing`]
+    Local(usize),
+
+    /// The own queue of the worker with the given index, only used by
+    /// [`Dispatch::RoundRobin`]
+    Pinned(usize),
+}
+
+/// Why [`Queue::pop`] returned without a job
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Exit {
+    /// The queue is closed and empty
+    Closed,
+
+    /// The worker has been asked to retire
+    Retired,
+
+    /// The worker has been idle for longer than the keep-alive
+    Expired,
+}
+
+/// An error returned by [`Queue::try_push`]
+pub(crate) enum TryPushError {
+    Full(Job),
+    Closed(Job),
+}
+
+impl Queue {
+    pub(crate) fn new(
+        custom: Option<Box<dyn JobQueue>>,
+        capacity: Option<usize>,
+        scheduler: Scheduler,
+        order: QueueOrder,
+        dispatch: Dispatch,
+        keep_alive: Option<Duration>,
+        min_workers: usize,
+    ) -> Self {
+        // A local queue would let jobs bypass the capacity or a synchronous handoff
+        let stealing = scheduler == Scheduler::WorkStealing
+            && dispatch == Dispatch::Shared
+            && capacity.is_none();
+
+        Self {
+            state: Mutex::new(State {
+                jobs: BTreeMap::new(),
+                custom,
+                assigned_jobs: BTreeMap::new(),
+                broadcasts: BTreeMap::new(),
+                order,
+                dispatch,
+                assigned: 0,
+                len: 0,
+                capacity,
+                retiring: 0,
+                workers: 0,
+                keep_alive,
+                min_workers,
+                notified: 0,
+            }),
+            locals: RwLock::new(BTreeMap::new()),
+            stealing,
+            dispatch,
+            closed: AtomicBool::new(false),
+            draining: AtomicBool::new(false),
+            paused: AtomicBool::new(false),
+            waiting: AtomicUsize::new(0),
+            popping: Mutex::new(()),
+            available: Condvar::new(),
+            space: Condvar::new(),
+        }
+    }
+
+    /// Pushes a job, blocking while the queue is full
+    ///
+    /// Hands the job back if the queue is closed or draining.
+    pub(crate) fn push(&self, target: Target, job: Job) -> Result<(), Job> {
+        let Err(job) = self.push_local(target, job) else {
+            return Ok(());
+        };
+
+        let mut state = self.lock();
+
+        while self.is_accepting() && self.is_full(&state) {
+            state = self.space.wait(state).unwrap_or_else(PoisonError::into_inner);
+        }
+
+        if !self.is_accepting() {
+            return Err(job);
+        }
+
+        self.push_locked(&mut state, target, job);
+
+        Ok(())
+    }
+
+    /// Pushes every job while holding the lock once, blocking while the queue is full
+    ///
+    /// Hands back the jobs which weren't pushed if the queue is closed or draining.
+    pub(crate) fn push_all(&self, target: Target, jobs: Vec<Job>) -> Result<(), Vec<Job>> {
+        let state = self.lock();
+        self.push_all_locked(state, target, jobs)
+    }
+
+    /// Pushes one of the jobs created from the number of workers onto the
+    /// broadcast queue of every worker which hasn't exited yet
+    ///
+    /// The jobs are pushed even if the queue is full, since waiting for room
+    /// would release the lock and let workers exit in between. Hands the jobs
+    /// back if the queue is closed or draining.
+    pub(crate) fn push_broadcast<F>(&self, make: F) -> Result<(), Vec<Job>>
+    where
+        F: FnOnce(usize) -> Vec<Job>,
+    {
+        let mut state = self.lock();
+        let jobs = make(state.broadcasts.len());
+
+        if !self.is_accepting() {
+            return Err(jobs);
+        }
+
+        state.len += jobs.len();
+
+        for (broadcasts, job) in state.broadcasts.values_mut().zip(jobs) {
+            broadcasts.push_back(job);
+        }
+
+        // Every worker has to wake up for its own job
+        self.available.notify_all();
+
+        Ok(())
+    }
+
+    fn push_all_locked(
+        &self,
+        mut state: MutexGuard<'_, State>,
+        target: Target,
+        jobs: Vec<Job>,
+    ) -> Result<(), Vec<Job>> {
+        let mut jobs = jobs.into_iter();
+
+        while let Some(job) = jobs.next() {
+            while self.is_accepting() && self.is_full(&state) {
+                state = self.space.wait(state).unwrap_or_else(PoisonError::into_inner);
+            }
+
+            if !self.is_accepting() {
+                return Err(std::iter::once(job).chain(jobs).collect());
+            }
+
+            self.push_locked(&mut state, target, job);
+        }
+
+        Ok(())
+    }
+
+    /// Pushes a job without blocking
+    pub(crate) fn try_push(&self, target: Target, job: Job) -> Result<(), TryPushError> {
+        let Err(job) = self.push_local(target, job) else {
+            return Ok(());
+        };
+
+        let mut state = self.lock();
+
+        if !self.is_accepting() {
+            return Err(TryPushError::Closed(job));
+        }
+
+        if self.is_full(&state) {
+            return Err(TryPushError::Full(job));
+        }
+
+        self.push_locked(&mut state, target, job);
+
+        Ok(())
+    }
+
+    /// Pushes a job without blocking, even if the queue is full
+    ///
+    /// Used by workers, which would wait for room only they can make. Hands
+    /// the job back if the queue is closed or draining.
+    pub(crate) fn push_unbounded(&self, target: Target, job: Job) -> Result<(), Job> {
+        let Err(job) = self.push_local(target, job) else {
+            return Ok(());
+        };
+
+        let mut state = self.lock();
+
+        if !self.is_accepting() {
+            return Err(job);
+        }
+
+        self.push_locked(&mut state, target, job);
+
+        Ok(())
+    }
+
+    /// Pushes a job without blocking, making room by removing the oldest job
+    /// of the lowest priority if the queue is full
+    ///
+    /// Returns the removed job, or hands the job back if the queue is closed or draining.
+    pub(crate) fn push_displacing(&self, target: Target, job: Job) -> Result<Option<Job>, Job> {
+        let mut state = self.lock();
+
+        if !self.is_accepting() {
+            return Err(job);
+        }
+
+        let displaced = if self.is_full(&state) { state.pop_oldest() } else { None };
+        self.push_locked(&mut state, target, job);
+
+        Ok(displaced)
+    }
+
+    /// Pops a job for the worker with the given index, blocking while the queue is empty
+    ///
+    /// Returns why the worker should exit instead once the queue is closed and
+    /// every job has been popped, if it has been asked to retire or if it has
+    /// been idle for longer than the keep-alive.
+    pub(crate) fn pop(&self, worker: usize) -> Result<Job, Exit> {
+        // The worker's own jobs don't need the shared state
+        if !self.is_paused() {
+            if let Some(job) = self.pop_local(worker) {
+                return Ok(job);
+            }
+        }
+
+        // Workers only wait for their own jobs with round-robin, so all of them have to wait
+        let mut popping = (self.dispatch != Dispatch::RoundRobin)
+            .then(|| self.popping.lock().unwrap_or_else(PoisonError::into_inner));
+
+        let mut state = self.lock();
+        let mut timed_out = false;
+
+        loop {
+            if let Some(job) = self.pop_broadcast(&mut state, worker) {
+                return Ok(job);
+            }
+
+            // A paused worker with a broadcast job has to stay until it has run it
+            let pinned = state.broadcasts.get(&worker).is_some_and(|jobs| !jobs.is_empty());
+
+            if state.retiring > 0 && !pinned {
+                state.retiring -= 1;
+                return Err(state.exit(worker, Exit::Retired));
+            }
+
+            if let Some(job) = self.pop_unpaused(&mut state, worker) {
+                if state.capacity.is_some() {
+                    self.space.notify_one();
+                }
+
+                return Ok(job);
+            }
+
+            if self.closed.load(Ordering::SeqCst) {
+                return Err(state.exit(worker, Exit::Closed));
+            }
+
+            if timed_out && state.workers > state.min_workers && !pinned {
+                return Err(state.exit(worker, Exit::Expired));
+            }
+
+            // The other workers can only get to their broadcast jobs once they hold `popping`
+            if state.broadcasts.values().any(|jobs| !jobs.is_empty()) {
+                drop(popping.take());
+            }
+
+            self.waiting.fetch_add(1, Ordering::SeqCst);
+
+            // A job pushed onto a local queue before this worker counted as
+            // waiting didn't wake it up. Missing one would only delay it, the
+            // worker owning the queue pops it before waiting itself.
+            if self.stealing && !self.is_paused() {
+                if let Some(job) = self.steal(worker) {
+                    self.waiting.fetch_sub(1, Ordering::SeqCst);
+                    return Ok(job);
+                }
+            }
+
+            // An idle worker is what a synchronous push waits for
+            if state.dispatch == Dispatch::Synchronous {
+                self.space.notify_one();
+            }
+
+            match state.keep_alive {
+                Some(keep_alive) => {
+                    let result = self.available.wait_timeout(state, keep_alive);
+                    let result = result.unwrap_or_else(PoisonError::into_inner);
+                    state = result.0;
+                    timed_out = result.1.timed_out();
+                }
+
+                None => {
+                    state = self.available.wait(state).unwrap_or_else(PoisonError::into_inner);
+                }
+            }
+
+            self.waiting.fetch_sub(1, Ordering::SeqCst);
+            state.notified = state.notified.saturating_sub(1);
+        }
+    }
+
+    /// Pops a job without blocking, for a thread which isn't one of the workers
+    ///
+    /// Returns `None` with [`Dispatch::RoundRobin`], where every job belongs to
+    /// a worker. Broadcast jobs are never popped.
+    pub(crate) fn try_pop(&self) -> Option<Job> {
+        let mut state = self.lock();
+
+        if state.dispatch == Dispatch::RoundRobin || self.is_paused() {
+            return None;
+        }
+
+        if let Some(job) = state.pop_global() {
+            state.len -= 1;
+
+            if state.capacity.is_some() {
+                self.space.notify_one();
+            }
+
+            return Some(job);
+        }
+
+        self.read_locals().values().find_map(|local| lock_local(local).pop_front())
+    }
+
+    /// Removes every queued job, those of the shared queue first in the order
+    /// they would be popped in
+    ///
+    /// Broadcast jobs stay queued for their workers.
+    pub(crate) fn take_all(&self) -> Vec<Job> {
+        let mut state = self.lock();
+        state.len = state.broadcasts.values().map(VecDeque::len).sum();
+
+        let mut jobs = state.take_global();
+        jobs.extend(state.assigned_jobs.values_mut().flat_map(std::mem::take));
+
+        for local in self.read_locals().values() {
+            jobs.extend(std::mem::take(&mut *lock_local(local)));
+        }
+
+        self.space.notify_all();
+
+        jobs
+    }
+
+    /// The number of jobs which can be pushed before the queue is full, `None` if it is unbounded
+    pub(crate) fn remaining_capacity(&self) -> Option<usize> {
+        let state = self.lock();
+
+        if state.dispatch == Dispatch::Synchronous {
+            return Some(self.waiting.load(Ordering::SeqCst).saturating_sub(state.len));
+        }
+
+        state.capacity.map(|capacity| capacity.saturating_sub(state.len))
+    }
+
+    /// Whether the queue accepts new jobs
+    pub(crate) fn is_accepting(&self) -> bool {
+        !self.closed.load(Ordering::SeqCst) && !self.draining.load(Ordering::SeqCst)
+    }
+
+    /// Whether the queue has been closed
+    pub(crate) fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::SeqCst)
+    }
+
+    /// The number of workers which haven't exited yet
+    pub(crate) fn workers(&self) -> usize {
+        self.lock().workers
+    }
+
+    /// Whether the worker with the given index has a queue of its own which
+    /// no other worker pops from
+    pub(crate) fn has_own_queue(&self, worker: usize) -> bool {
+        let state = self.lock();
+        state.dispatch == Dispatch::RoundRobin && state.assigned_jobs.contains_key(&worker)
+    }
+
+    /// Adds a worker with the given index, including its local queue
+    pub(crate) fn register(&self, worker: usize) {
+        let mut state = self.lock();
+        state.workers += 1;
+        state.broadcasts.insert(worker, VecDeque::new());
+
+        match state.dispatch {
+            // Take over the jobs which were queued while there was no worker
+            Dispatch::RoundRobin => {
+                let jobs = state.take_global().into();
+                state.assigned_jobs.insert(worker, jobs);
+            }
+
+            _ if self.stealing => {
+                let mut locals = self.locals.write().unwrap_or_else(PoisonError::into_inner);
+                locals.insert(worker, Mutex::new(VecDeque::new()));
+            }
+
+            _ => {}
+        }
+    }
+
+    /// Removes the local queue of an exiting worker, moving its jobs to the shared queue
+    ///
+    /// With round-robin, the jobs are assigned to the remaining workers instead.
+    pub(crate) fn unregister(&self, worker: usize) {
+        let mut state = self.lock();
+
+        if let Some(jobs) = state.assigned_jobs.remove(&worker) {
+            if jobs.is_empty() {
+                return;
+            }
+
+            if !state.assigned_jobs.is_empty() {
+                for job in jobs {
+                    state.assign(job);
+                }
+            } else {
+                for job in jobs {
+                    state.push_global(0, job);
+                }
+            }
+
+            self.available.notify_all();
+        }
+
+        let local = self.locals.write().unwrap_or_else(PoisonError::into_inner).remove(&worker);
+
+        if let Some(local) = local {
+            let jobs = local.into_inner().unwrap_or_else(PoisonError::into_inner);
+
+            if jobs.is_empty() {
+                return;
+            }
+
+            state.len += jobs.len();
+
+            for job in jobs {
+                state.push_global(0, job);
+            }
+
+            self.available.notify_all();
+        }
+    }
+
+    /// Removes a registered worker whose thread couldn't be spawned
+    ///
+    /// Returns the broadcast jobs which were pushed for the worker in the meantime.
+    pub(crate) fn forget(&self, worker: usize) -> VecDeque<Job> {
+        let mut state = self.lock();
+        state.workers -= 1;
+
+        let jobs = state.broadcasts.remove(&worker).unwrap_or_default();
+        state.len -= jobs.len();
+
+        drop(state);
+        self.unregister(worker);
+
+        jobs
+    }
+
+    /// Makes the next `count` calls to `pop` return `Exit::Retired` instead of a job
+    ///
+    /// Returns `false` without retiring any worker if this would retire all of them.
+    pub(crate) fn retire(&self, count: usize) -> bool {
+        let mut state = self.lock();
+
+        if count >= state.workers {
+            return false;
+        }
+
+        state.retiring += count;
+        self.available.notify_all();
+
+        true
+    }
+
+    /// Sets whether new jobs are rejected, without affecting the queued ones
+    pub(crate) fn set_draining(&self, draining: bool) {
+        let _state = self.lock();
+        self.draining.store(draining, Ordering::SeqCst);
+
+        // Wake up blocked pushes so they can fail
+        self.space.notify_all();
+    }
+
+    /// Sets whether workers stop popping jobs, without affecting the queued ones
+    pub(crate) fn set_paused(&self, paused: bool) {
+        let _state = self.lock();
+        self.paused.store(paused, Ordering::SeqCst);
+
+        // Wake up the waiting workers so they can pop again
+        if !paused {
+            self.available.notify_all();
+        }
+    }
+
+    /// Whether workers have been paused, which closing the queue ends so the
+    /// queued jobs still run
+    pub(crate) fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::SeqCst) && !self.closed.load(Ordering::SeqCst)
+    }
+
+    /// Closes the queue, the jobs which are already queued can still be popped
+    pub(crate) fn close(&self) {
+        let state = self.lock();
+        self.closed.store(true, Ordering::SeqCst);
+        drop(state);
+
+        self.available.notify_all();
+        self.space.notify_all();
+    }
+
+    /// Locks the state, recovering it if a thread panicked while holding the lock
+    ///
+    /// The state is only modified by this module, which never leaves it
+    /// inconsistent across a panic.
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Reads the local queues, recovering them if a thread panicked while holding the lock
+    fn read_locals(&self) -> RwLockReadGuard<'_, BTreeMap<usize, Mutex<VecDeque<Job>>>> {
+        self.locals.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Pushes a job onto the local queue of a worker without locking `state`
+    ///
+    /// Hands the job back if the target isn't a local queue, or if the queue
+    /// isn't accepting jobs, so the caller falls back to the shared state.
+    fn push_local(&self, target: Target, job: Job) -> Result<(), Job> {
+        let Target::Local(worker) = target else {
+            return Err(job);
+        };
+
+        if !self.is_accepting() {
+            return Err(job);
+        }
+
+        {
+            let locals = self.read_locals();
+
+            let Some(local) = locals.get(&worker) else {
+                return Err(job);
+            };
+
+            lock_local(local).push_back(job);
+        }
+
+        // Only lock the state if there is a waiting worker which could steal the job
+        if self.waiting.load(Ordering::SeqCst) > 0 {
+            let mut state = self.lock();
+            self.notify_one(&mut state);
+        }
+
+        Ok(())
+    }
+
+    /// Pops the most recently pushed job of the worker's own local queue
+    fn pop_local(&self, worker: usize) -> Option<Job> {
+        lock_local(self.read_locals().get(&worker)?).pop_back()
+    }
+
+    /// Pops the oldest job of another worker's local queue
+    fn steal(&self, worker: usize) -> Option<Job> {
+        self.read_locals()
+            .iter()
+            .filter(|(index, _)| **index != worker)
+            .find_map(|(_, local)| lock_local(local).pop_front())
+    }
+
+    /// Pops the next broadcast job of the worker, unless the queue is paused
+    fn pop_broadcast(&self, state: &mut State, worker: usize) -> Option<Job> {
+        if self.is_paused() {
+            return None;
+        }
+
+        let job = state.broadcasts.get_mut(&worker)?.pop_front()?;
+        state.len -= 1;
+
+        if state.capacity.is_some() {
+            self.space.notify_one();
+        }
+
+        Some(job)
+    }
+
+    /// Pops a job like [`Queue::pop`] without blocking, unless the queue is paused
+    fn pop_unpaused(&self, state: &mut State, worker: usize) -> Option<Job> {
+        if self.is_paused() {
+            return None;
+        }
+
+        if state.dispatch == Dispatch::RoundRobin {
+            return state.pop_assigned(worker);
+        }
+
+        if let Some(job) = self.pop_local(worker) {
+            return Some(job);
+        }
+
+        if let Some(job) = state.pop_global() {
+            state.len -= 1;
+            return Some(job);
+        }
+
+        self.steal(worker)
+    }
+
+    fn is_full(&self, state: &State) -> bool {
+        // Every queued job has been handed to a waiting worker which didn't wake up yet
+        if state.dispatch == Dispatch::Synchronous {
+            return state.len >= self.waiting.load(Ordering::SeqCst);
+        }
+
+        state.capacity.is_some_and(|capacity| state.len >= capacity)
+    }
+
+    /// Wakes up a waiting thread, unless it is already on its way
+    fn notify_one(&self, state: &mut State) {
+        if self.waiting.load(Ordering::SeqCst) > state.notified {
+            state.notified += 1;
+            self.available.notify_one();
+        }
+    }
+
+    fn push_locked(&self, state: &mut State, target: Target, job: Job) {
+        if state.dispatch == Dispatch::RoundRobin {
+            match target {
+                Target::Pinned(worker) if state.assigned_jobs.contains_key(&worker) => {
+                    state.assigned_jobs.get_mut(&worker).unwrap().push_back(job);
+                }
+
+                // The worker exited in the meantime, so hand the job to another one
+                _ => state.assign(job),
+            }
+
+            state.len += 1;
+
+            // Only the worker the job was assigned to can pop it
+            self.available.notify_all();
+            return;
+        }
+
+        match target {
+            // Local queues are pushed onto without the lock, so the worker is
+            // about to exit or the local queues aren't used
+            Target::Local(_) | Target::Pinned(_) => state.push_global(0, job),
+            Target::Global(priority) => state.push_global(priority, job),
+        }
+
+        state.len += 1;
+        self.notify_one(state);
+    }
+}
+
+/// Locks a local queue, recovering it if a thread panicked while holding the lock
+fn lock_local(local: &Mutex<VecDeque<Job>>) -> MutexGuard<'_, VecDeque<Job>> {
+    local.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+impl State {
+    /// Removes an exiting worker, returning why it exits
+    fn exit(&mut self, worker: usize, exit: Exit) -> Exit {
+        self.workers -= 1;
+        self.broadcasts.remove(&worker);
+        exit
+    }
+
+    /// Pops the next job assigned to the worker by [`Dispatch::RoundRobin`]
+    fn pop_assigned(&mut self, worker: usize) -> Option<Job> {
+        let jobs = self.assigned_jobs.get_mut(&worker)?;
+
+        let job = match self.order {
+            QueueOrder::Fifo => jobs.pop_front(),
+            QueueOrder::Lifo => jobs.pop_back(),
+        }?;
+
+        self.len -= 1;
+        Some(job)
+    }
+
+    /// Pops the oldest job of the lowest priority, falling back to the assigned queues
+    ///
+    /// A custom queue doesn't know which job is the oldest, so it pops its next one.
+    fn pop_oldest(&mut self) -> Option<Job> {
+        if let Some(custom) = &mut self.custom {
+            let job = custom.pop()?.job;
+            self.len -= 1;
+            return Some(job);
+        }
+
+        let job = match self.jobs.first_entry() {
+            Some(mut entry) => {
+                let job = entry.get_mut().pop_front();
+
+                if entry.get().is_empty() {
+                    entry.remove();
+                }
+
+                job
+            }
+
+            None => self.assigned_jobs.values_mut().find_map(VecDeque::pop_front),
+        }?;
+
+        self.len -= 1;
+        Some(job)
+    }
+
+    /// Pushes a job onto the local queue of the next worker in turn
+    ///
+    /// Falls back to the shared queue if there are no workers, whose jobs are
+    /// taken over by the next worker to register.
+    fn assign(&mut self, job: Job) {
+        if self.assigned_jobs.is_empty() {
+            self.push_global(0, job);
+            return;
+        }
+
+        let index = self.assigned % self.assigned_jobs.len();
+        self.assigned = self.assigned.wrapping_add(1);
+
+        self.assigned_jobs.values_mut().nth(index).unwrap().push_back(job);
+    }
+
+    /// Pushes a job onto the shared queue
+    fn push_global(&mut self, priority: u8, job: Job) {
+        match &mut self.custom {
+            Some(custom) => custom.push(QueuedJob::new(job, priority)),
+            None => self.jobs.entry(priority).or_default().push_back(job),
+        }
+    }
+
+    /// Removes every job of the shared queue in the order they would be popped in
+    fn take_global(&mut self) -> Vec<Job> {
+        match &mut self.custom {
+            Some(custom) => std::iter::from_fn(|| custom.pop()).map(|queued| queued.job).collect(),
+            None => {
+                let order = self.order;
+
+                std::mem::take(&mut self.jobs)
+                    .into_values()
+                    .rev()
+                    .flat_map(|mut jobs| {
+                        if order == QueueOrder::Lifo {
+                            jobs.make_contiguous().reverse();
+                        }
+
+                        jobs
+                    })
+                    .collect()
+            }
+        }
+    }
+
+    /// Pops the job with the highest priority from the shared queue
+    fn pop_global(&mut self) -> Option<Job> {
+        if let Some(custom) = &mut self.custom {
+            return custom.pop().map(|queued| queued.job);
+        }
+
+        let mut entry = self.jobs.last_entry()?;
+
+        let job = match self.order {
+            QueueOrder::Fifo => entry.get_mut().pop_front(),
+            QueueOrder::Lifo => entry.get_mut().pop_back(),
+        };
+
+        if entry.get().is_empty() {
+            entry.remove();
+        }
+
+        job
+    }
+}
@@ -0,0 +1,57 @@
+use std::sync::{Mutex, PoisonError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// A token bucket limiting how many jobs are started per second
+///
+/// The bucket holds up to one second worth of tokens and starts out full,
+/// so a burst of up to `per_second` jobs starts right away.
+pub(crate) struct RateLimiter {
+    per_second: f64,
+
+    bucket: Mutex<Bucket>,
+}
+
+struct Bucket {
+    tokens: f64,
+
+    /// When `tokens` was last refilled
+    refilled: Instant,
+}
+
+impl RateLimiter {
+    pub(crate) fn new(per_second: u32) -> Self {
+        Self {
+            per_second: f64::from(per_second),
+            bucket: Mutex::new(Bucket {
+                tokens: f64::from(per_second),
+                refilled: Instant::now(),
+            }),
+        }
+    }
+
+    /// Blocks until a token is available and takes it
+    pub(crate) fn acquire(&self) {
+        loop {
+            let wait = {
+                let mut bucket = self.bucket.lock().unwrap_or_else(PoisonError::into_inner);
+
+                let now = Instant::now();
+                let elapsed = now.duration_since(bucket.refilled).as_secs_f64();
+
+                bucket.tokens = (bucket.tokens + elapsed * self.per_second).min(self.per_second);
+                bucket.refilled = now;
+
+                if bucket.tokens >= 1.0 {
+                    bucket.tokens -= 1.0;
+                    return;
+                }
+
+                (1.0 - bucket.tokens) / self.per_second
+            };
+
+            // Sleep without holding the lock, another worker may get the token first
+            thread::sleep(Duration::from_secs_f64(wait));
+        }
+    }
+}
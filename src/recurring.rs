@@ -0,0 +1,83 @@
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, TryLockError};
+use std::time::Duration;
+
+/// A handle to a job scheduled with
+/// [`ThreadPool::schedule_recurring`](crate::ThreadPool::schedule_recurring)
+///
+/// Dropping a `RecurringHandle` doesn't stop the job, it keeps running until
+/// the pool shuts down.
+#[derive(Clone)]
+pub struct RecurringHandle {
+    task: Arc<Recurring>,
+}
+
+impl RecurringHandle {
+    pub(crate) fn new(task: Arc<Recurring>) -> Self {
+        Self { task }
+    }
+
+    /// Stops the job from running again, a run which has already started
+    /// isn't interrupted
+    pub fn stop(&self) {
+        self.task.stopped.store(true, Ordering::SeqCst);
+    }
+
+    /// Returns whether [`RecurringHandle::stop`] has been called
+    pub fn is_stopped(&self) -> bool {
+        self.task.is_stopped()
+    }
+}
+
+impl fmt::Debug for RecurringHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RecurringHandle")
+            .field("interval", &self.task.interval)
+            .field("stopped", &self.is_stopped())
+            .finish()
+    }
+}
+
+/// A job which the timer submits every `interval`
+pub(crate) struct Recurring {
+    f: Mutex<Box<dyn FnMut() + Send>>,
+
+    pub(crate) interval: Duration,
+
+    stopped: AtomicBool,
+}
+
+impl Recurring {
+    pub(crate) fn new<F>(interval: Duration, f: F) -> Self
+    where
+        F: FnMut() + Send + 'static,
+    {
+        Self {
+            f: Mutex::new(Box::new(f)),
+            interval,
+            stopped: AtomicBool::new(false),
+        }
+    }
+
+    pub(crate) fn is_stopped(&self) -> bool {
+        self.stopped.load(Ordering::SeqCst)
+    }
+
+    /// Runs the job once, unless it has been stopped or the previous run
+    /// hasn't finished yet
+    pub(crate) fn run(&self) {
+        if self.is_stopped() {
+            return;
+        }
+
+        // A run which panicked has left nothing half-done that later runs depend on
+        let mut f = match self.f.try_lock() {
+            Ok(f) => f,
+            Err(TryLockError::Poisoned(err)) => err.into_inner(),
+            Err(TryLockError::WouldBlock) => return,
+        };
+
+        f();
+    }
+}
@@ -0,0 +1,209 @@
+use std::collections::VecDeque;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError};
+
+use crate::{ThreadPool, ThreadPoolBuilder};
+
+/// A fixed set of worker threads shared by several logical pools
+///
+/// Libraries which each create their own [`ThreadPool`] can oversubscribe the
+/// machine. Logical pools created with [`ThreadPool::new_shared`] only queue
+/// their jobs, which are then executed by the threads of the registry, so the
+/// number of threads stays capped however many logical pools there are.
+///
+/// The threads keep running as long as the registry or any of its logical
+/// pools is alive.
+#[derive(Clone)]
+pub struct PoolRegistry {
+    pool: ThreadPool,
+}
+
+impl PoolRegistry {
+    /// Creates a registry with `num_threads` worker threads
+    ///
+    /// # Panics
+    ///
+    /// Panics if `num_threads` is zero.
+    pub fn new(num_threads: usize) -> Self {
+        let pool = ThreadPoolBuilder::new()
+            .num_threads(num_threads)
+            .thread_name_prefix("threatpool-registry")
+            .build();
+
+        Self { pool }
+    }
+
+    /// Returns the number of worker threads
+    pub fn num_threads(&self) -> usize {
+        self.pool.size()
+    }
+}
+
+/// A pool which runs its jobs on the threads of a [`PoolRegistry`]
+///
+/// See [`ThreadPool::new_shared`]. Cloning a `LogicalPool` returns another
+/// handle to the same pool.
+#[derive(Clone)]
+pub struct LogicalPool {
+    state: Arc<LogicalState>,
+
+    /// The pool of the registry
+    pool: ThreadPool,
+}
+
+struct LogicalState {
+    queue: Mutex<LogicalQueue>,
+
+    /// Notified whenever `pending` drops to zero
+    idle: Condvar,
+
+    /// The maximum number of jobs running at once
+    size: usize,
+}
+
+struct LogicalQueue {
+    jobs: VecDeque<Box<dyn FnOnce() + Send + 'static>>,
+
+    /// The number of jobs submitted to the registry which run this pool's jobs
+    running: usize,
+
+    /// The number of jobs which are queued or running
+    pending: usize,
+}
+
+impl LogicalState {
+    /// Locks the queue, recovering it if a thread panicked while holding the lock
+    fn lock(&self) -> MutexGuard<'_, LogicalQueue> {
+        self.queue.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Submits a job to the registry which runs the next job of the logical pool
+///
+/// Every runner executes one job and then submits itself again if there are
+/// more, so the logical pools take turns on the threads of the registry.
+fn submit_runner(state: Arc<LogicalState>, pool: ThreadPool) {
+    let registry = pool.clone();
+
+    registry.execute(move || {
+        let mut queue = state.lock();
+
+        // Another runner got to the job first
+        let Some(job) = queue.jobs.pop_front() else {
+            queue.running -= 1;
+            return;
+        };
+
+        drop(queue);
+
+        // The panic message has already been printed by the panic hook
+        let _ = panic::catch_unwind(AssertUnwindSafe(job));
+
+        let mut queue = state.lock();
+        queue.pending -= 1;
+
+        if queue.pending == 0 {
+            state.idle.notify_all();
+        }
+
+        if queue.jobs.is_empty() {
+            queue.running -= 1;
+        } else {
+            drop(queue);
+            submit_runner(state, pool);
+        }
+    });
+}
+
+impl LogicalPool {
+    /// Executes a given job on the threads of the registry
+    ///
+    /// At most `size` jobs of this pool are running at once.
+    pub fn execute<F>(&self, f: F)
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        let mut queue = self.state.lock();
+        queue.jobs.push_back(Box::new(f));
+        queue.pending += 1;
+
+        if queue.running < self.state.size {
+            queue.running += 1;
+            drop(queue);
+
+            submit_runner(Arc::clone(&self.state), self.pool.clone());
+        }
+    }
+
+    /// Blocks until every job submitted to this pool so far has finished
+    ///
+    /// Jobs of other logical pools aren't waited for.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called from within a job of the same registry, since the job
+    /// might wait for itself to finish.
+    pub fn join(&self) {
+        self.pool.inner.shared.assert_not_worker("join");
+
+        let mut queue = self.state.lock();
+
+        while queue.pending > 0 {
+            queue = self.state.idle.wait(queue).unwrap_or_else(PoisonError::into_inner);
+        }
+    }
+
+    /// Returns the maximum number of jobs of this pool running at once
+    pub fn size(&self) -> usize {
+        self.state.size
+    }
+
+    /// Returns the number of jobs which are queued or running
+    pub fn pending(&self) -> usize {
+        self.state.lock().pending
+    }
+}
+
+impl ThreadPool {
+    /// Creates a logical pool which runs its jobs on the threads of `registry`
+    ///
+    /// The logical pool doesn't spawn any threads, at most `size` of its jobs
+    /// run at once. Logical pools sharing a registry take turns after every job.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is zero.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use threatpool::{PoolRegistry, ThreadPool};
+    ///
+    /// let registry = PoolRegistry::new(4);
+    ///
+    /// let io = ThreadPool::new_shared(2, &registry);
+    /// let compute = ThreadPool::new_shared(4, &registry);
+    ///
+    /// io.execute(|| println!("Hello from io"));
+    /// compute.execute(|| println!("Hello from compute"));
+    ///
+    /// io.join();
+    /// compute.join();
+    /// ```
+    pub fn new_shared(size: usize, registry: &PoolRegistry) -> LogicalPool {
+        assert!(size > 0, "a logical pool needs to run at least one job at once");
+
+        LogicalPool {
+            state: Arc::new(LogicalState {
+                queue: Mutex::new(LogicalQueue {
+                    jobs: VecDeque::new(),
+                    running: 0,
+                    pending: 0,
+                }),
+                idle: Condvar::new(),
+                size,
+            }),
+            pool: registry.pool.clone(),
+        }
+    }
+}
@@ -0,0 +1,147 @@
+use std::any::Any;
+use std::marker::PhantomData;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{Arc, Condvar, Mutex, PoisonError};
+
+use crate::ThreadPool;
+
+/// A scope to spawn jobs which may borrow data from outside of it
+///
+/// See [`ThreadPool::scope`].
+pub struct Scope<'scope, 'env: 'scope> {
+    pool: &'scope ThreadPool,
+
+    state: Arc<ScopeState>,
+
+    /// Invariance over `'scope`, just like `std::thread::Scope`
+    scope: PhantomData<&'scope mut &'scope ()>,
+
+    /// Invariance over `'env`, just like `std::thread::Scope`
+    env: PhantomData<&'env mut &'env ()>,
+}
+
+struct ScopeState {
+    /// The number of spawned jobs which haven't finished yet
+    running: Mutex<usize>,
+
+    /// Notified whenever `running` drops to zero
+    done: Condvar,
+
+    /// The payload of the first spawned job which panicked
+    panic: Mutex<Option<Box<dyn Any + Send + 'static>>>,
+}
+
+impl ScopeState {
+    fn finish_job(&self) {
+        let mut running = self.running.lock().unwrap_or_else(PoisonError::into_inner);
+        *running -= 1;
+
+        if *running == 0 {
+            self.done.notify_all();
+        }
+    }
+}
+
+impl<'scope, 'env> Scope<'scope, 'env> {
+    /// Spawns a job on the pool which may borrow data living longer than the scope
+    ///
+    /// # Panics
+    ///
+    /// Panics if the pool is no longer accepting jobs, or if the bounded queue
+    /// is full with [`RejectionPolicy::Abort`](crate::RejectionPolicy::Abort).
+    pub fn spawn<F>(&'scope self, f: F)
+    where
+        F: FnOnce() + Send + 'scope,
+    {
+        *self.state.running.lock().unwrap_or_else(PoisonError::into_inner) += 1;
+
+        let state = Arc::clone(&self.state);
+
+        let job: Box<dyn FnOnce() + Send + 'scope> = Box::new(move || {
+            if let Err(payload) = panic::catch_unwind(AssertUnwindSafe(f)) {
+                state.panic.lock().unwrap_or_else(PoisonError::into_inner).get_or_insert(payload);
+            }
+
+            state.finish_job();
+        });
+
+        // SAFETY: `ThreadPool::scope` doesn't return before every spawned job
+        // has finished, so nothing the job borrows can be dropped while it runs.
+        let job: Box<dyn FnOnce() + Send + 'static> = unsafe { std::mem::transmute(job) };
+
+        if let Err(err) = self.pool.try_execute(job) {
+            let error = err.error();
+
+            // The job never ran, so it won't decrement `running` on its own
+            drop(err.into_inner());
+            self.state.finish_job();
+
+            panic!("failed to spawn a scoped job: {error}");
+        }
+    }
+}
+
+impl ThreadPool {
+    /// Creates a scope for spawning jobs which may borrow non-`'static` data
+    ///
+    /// All jobs spawned through the [`Scope`] are joined before this returns.
+    /// If any of them panicked, this panics as well once all are done.
+    ///
+    /// Calling `scope` from within a job of the same pool can deadlock if all
+    /// workers end up waiting for scoped jobs.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::sync::atomic::{AtomicUsize, Ordering};
+    /// use threatpool::ThreadPool;
+    ///
+    /// let pool = ThreadPool::new(4);
+    /// let numbers = vec![1, 2, 3, 4];
+    /// let sum = AtomicUsize::new(0);
+    ///
+    /// pool.scope(|scope| {
+    ///     for number in &numbers {
+    ///         scope.spawn(|| {
+    ///             sum.fetch_add(*number, Ordering::SeqCst);
+    ///         });
+    ///     }
+    /// });
+    ///
+    /// assert_eq!(sum.into_inner(), 10);
+    /// ```
+    pub fn scope<'env, F, T>(&self, f: F) -> T
+    where
+        F: for<'scope> FnOnce(&'scope Scope<'scope, 'env>) -> T,
+    {
+        let scope = Scope {
+            pool: self,
+            state: Arc::new(ScopeState {
+                running: Mutex::new(0),
+                done: Condvar::new(),
+                panic: Mutex::new(None),
+            }),
+            scope: PhantomData,
+            env: PhantomData,
+        };
+
+        // The spawned jobs have to be joined even if `f` panics
+        let result = panic::catch_unwind(AssertUnwindSafe(|| f(&scope)));
+
+        let mut running = scope.state.running.lock().unwrap_or_else(PoisonError::into_inner);
+        while *running > 0 {
+            running = scope.state.done.wait(running).unwrap_or_else(PoisonError::into_inner);
+        }
+        drop(running);
+
+        match result {
+            Err(payload) => panic::resume_unwind(payload),
+
+            Ok(_) if scope.state.panic.lock().unwrap_or_else(PoisonError::into_inner).is_some() => {
+                panic!("a scoped job panicked");
+            }
+
+            Ok(result) => result,
+        }
+    }
+}
@@ -0,0 +1,29 @@
+/// The lifecycle state of a pool, see [`ThreadPool::state`](crate::ThreadPool::state)
+///
+/// A pool only ever moves forward through these states, except that a
+/// draining pool becomes running again when it is reopened.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum PoolState {
+    /// The pool accepts and executes jobs
+    Running,
+
+    /// The pool executes the queued jobs but doesn't accept new ones
+    Draining,
+
+    /// The pool has been shut down and the workers exit once the queue is empty
+    ShuttingDown,
+
+    /// Every worker has exited
+    Stopped,
+}
+
+impl PoolState {
+    pub(crate) fn from_u8(state: u8) -> Self {
+        match state {
+            0 => Self::Running,
+            1 => Self::Draining,
+            2 => Self::ShuttingDown,
+            _ => Self::Stopped,
+        }
+    }
+}
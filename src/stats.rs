@@ -0,0 +1,24 @@
+/// A snapshot of the pool's metrics, see [`ThreadPool::stats`](crate::ThreadPool::stats)
+///
+/// The counters are read one after another, so they may be slightly out of
+/// sync with each other while jobs are moving through the pool.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct PoolStats {
+    /// The number of jobs currently executing
+    pub active: usize,
+
+    /// The number of jobs waiting in the queue
+    pub queued: usize,
+
+    /// The number of jobs which have finished
+    pub completed: u64,
+
+    /// The number of jobs which have been accepted
+    pub submitted: u64,
+
+    /// The number of finished jobs which panicked
+    pub failed: u64,
+
+    /// The number of workers waiting for a job
+    pub idle: usize,
+}
@@ -0,0 +1,218 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError};
+use std::thread;
+use std::time::Instant;
+
+use crate::recurring::Recurring;
+use crate::{Job, Shared};
+
+/// A thread which holds back delayed jobs and submits them once they are due
+pub(crate) struct Timer {
+    state: Arc<TimerState>,
+
+    thread: Option<thread::JoinHandle<()>>,
+}
+
+struct TimerState {
+    entries: Mutex<Entries>,
+
+    /// Notified whenever an entry is added or the timer is closed
+    changed: Condvar,
+}
+
+struct Entries {
+    heap: BinaryHeap<Entry>,
+
+    /// The sequence number of the next entry
+    seq: u64,
+
+    /// Whether the timer has been closed, which drops every remaining entry
+    closed: bool,
+}
+
+/// A delayed task ordered by its deadline and then by its sequence number
+struct Entry {
+    deadline: Instant,
+    seq: u64,
+    task: Task,
+}
+
+enum Task {
+    /// Submitted once
+    Once(Job),
+
+    /// Submitted every interval until it is stopped
+    Recurring(Arc<Recurring>),
+}
+
+impl PartialEq for Entry {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Entry {}
+
+impl PartialOrd for Entry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Entry {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // The heap pops its greatest element, so earlier entries are greater
+        other
+            .deadline
+            .cmp(&self.deadline)
+            .then_with(|| other.seq.cmp(&self.seq))
+    }
+}
+
+impl Timer {
+    /// Spawns the timer thread which submits due jobs to the pool
+    pub(crate) fn new(shared: Arc<Shared>) -> Self {
+        let state = Arc::new(TimerState {
+            entries: Mutex::new(Entries {
+                heap: BinaryHeap::new(),
+                seq: 0,
+                closed: false,
+            }),
+            changed: Condvar::new(),
+        });
+
+        let timer_state = Arc::clone(&state);
+
+        let thread = thread::Builder::new()
+            .name("threatpool-timer".into())
+            .spawn(move || timer_state.run(&shared))
+            .expect("failed to spawn timer thread");
+
+        Self {
+            state,
+            thread: Some(thread),
+        }
+    }
+
+    /// Schedules a job to be submitted once `deadline` has passed
+    pub(crate) fn schedule(&self, deadline: Instant, job: Job) {
+        self.state.insert(deadline, Task::Once(job));
+    }
+
+    /// Schedules a job to be submitted every interval, the first time once
+    /// the interval has passed
+    pub(crate) fn schedule_recurring(&self, task: Arc<Recurring>) {
+        self.state.insert(Instant::now() + task.interval, Task::Recurring(task));
+    }
+
+    /// Closes the timer, dropping every job which isn't due yet
+    pub(crate) fn close(&self) {
+        let jobs = {
+            let mut entries = self.state.lock();
+            entries.closed = true;
+            std::mem::take(&mut entries.heap)
+        };
+
+        self.state.changed.notify_one();
+
+        // Drop the jobs outside of the lock
+        drop(jobs);
+    }
+
+    /// Takes the handle of the timer thread, which detaches it unless joined
+    pub(crate) fn take_thread(&mut self) -> Option<thread::JoinHandle<()>> {
+        self.thread.take()
+    }
+
+    /// Waits for the timer thread to exit
+    pub(crate) fn join(&mut self) {
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Entries {
+    fn insert(&mut self, deadline: Instant, task: Task) {
+        let seq = self.seq;
+        self.seq += 1;
+
+        self.heap.push(Entry { deadline, seq, task });
+    }
+}
+
+impl TimerState {
+    /// Locks the entries, recovering them if a thread panicked while holding the lock
+    fn lock(&self) -> MutexGuard<'_, Entries> {
+        self.entries.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn insert(&self, deadline: Instant, task: Task) {
+        let mut entries = self.lock();
+
+        if entries.closed {
+            return;
+        }
+
+        entries.insert(deadline, task);
+        self.changed.notify_one();
+    }
+
+    fn run(&self, shared: &Shared) {
+        let mut entries = self.lock();
+
+        loop {
+            if entries.closed {
+                return;
+            }
+
+            let now = Instant::now();
+
+            match entries.heap.peek() {
+                Some(entry) if entry.deadline <= now => {
+                    let entry = entries.heap.pop().unwrap();
+
+                    // Pushing may block on a full queue, so don't hold the lock
+                    drop(entries);
+
+                    let rearm = match entry.task {
+                        Task::Once(job) => {
+                            let _ = shared.push(0, job);
+                            None
+                        }
+
+                        Task::Recurring(task) if task.is_stopped() => None,
+
+                        Task::Recurring(task) => {
+                            let run = Arc::clone(&task);
+
+                            // Don't catch up on missed runs if the timer fell behind
+                            let next = (entry.deadline + task.interval).max(Instant::now());
+
+                            shared.push(0, Job::new(move || run.run())).ok().map(|_| (next, task))
+                        }
+                    };
+
+                    entries = self.lock();
+
+                    if let Some((next, task)) = rearm {
+                        if !entries.closed {
+                            entries.insert(next, Task::Recurring(task));
+                        }
+                    }
+                }
+
+                Some(entry) => {
+                    let timeout = entry.deadline - now;
+                    let result = self.changed.wait_timeout(entries, timeout);
+                    entries = result.unwrap_or_else(PoisonError::into_inner).0;
+                }
+
+                None => {
+                    entries = self.changed.wait(entries).unwrap_or_else(PoisonError::into_inner);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,61 @@
+use std::io;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{Arc, PoisonError, Weak};
+use std::thread;
+use std::time::Duration;
+
+use crate::Inner;
+
+/// Spawns a thread which reports the jobs running for longer than `threshold`
+///
+/// The thread only holds on to the pool while checking it, and exits once
+/// the pool has been dropped. It is never joined, since the pool may be
+/// dropped on the thread itself.
+pub(crate) fn spawn(inner: Weak<Inner>, threshold: Duration) -> io::Result<()> {
+    // Checking a few times per threshold keeps reports reasonably on time
+    let interval = (threshold / 4).max(Duration::from_millis(1));
+
+    thread::Builder::new()
+        .name("threatpool-watchdog".into())
+        .spawn(move || loop {
+            thread::sleep(interval);
+
+            let Some(inner) = inner.upgrade() else {
+                return;
+            };
+
+            if inner.shared.queue.is_closed() {
+                return;
+            }
+
+            check(&inner, threshold);
+        })?;
+
+    Ok(())
+}
+
+/// Reports every job which has newly exceeded the threshold
+fn check(inner: &Arc<Inner>, threshold: Duration) {
+    let slow: Vec<_> = inner
+        .workers
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .iter()
+        .filter_map(|worker| worker.slot.take_slow(threshold))
+        .collect();
+
+    for (name, elapsed) in slow {
+        match &inner.builder.on_slow_job {
+            Some(callback) => {
+                // A panicking callback must not stop the watchdog
+                let report = || callback(name.as_deref(), elapsed);
+                let _ = panic::catch_unwind(AssertUnwindSafe(report));
+            }
+
+            None => {
+                let name = name.as_deref().unwrap_or("an unnamed job");
+                eprintln!("threatpool: {name} has been running for {elapsed:?}");
+            }
+        }
+    }
+}